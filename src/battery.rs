@@ -3,9 +3,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     command,
+    config::CONFIG,
     daemon::{DaemonItem, DaemonMessage, DaemonReply},
     error::DaemonError,
-    ICON_EXT, NOTIFICATION_ID, NOTIFICATION_TIMEOUT,
+    icon::{self, IconStyle},
+    locale::translate,
+    notification::Notification,
+    tuples::Tuples,
 };
 
 #[derive(PartialEq, Eq, Debug)]
@@ -25,7 +29,10 @@ pub enum BatteryGetCommands {
     #[command(alias = "t")]
     Time,
     #[command(alias = "i")]
-    Icon,
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
@@ -33,7 +40,7 @@ pub enum BatteryItem {
     State,
     Percent,
     Time,
-    Icon,
+    Icon(Option<IconStyle>),
     All,
 }
 
@@ -123,35 +130,38 @@ impl Battery {
     }
 
     #[must_use]
-    pub fn get_icon(state: &BatteryState, percent: u32) -> String {
-        if state == &BatteryState::NotCharging {
-            "battery-missing".to_string()
-        } else {
-            format!(
-                "battery-{:0>3}{}",
-                percent / 10 * 10,
-                match state {
-                    BatteryState::Charging => "-charging",
-                    // BatteryState::FullyCharged => "-charged",
-                    _ => "",
-                }
-            )
-        }
+    pub fn get_icon(style: IconStyle, state: &BatteryState, percent: u32) -> String {
+        // Names of the states in the icon map
+        let state = match state {
+            BatteryState::FullyCharged => "fully_charged",
+            BatteryState::Charging => "charging",
+            BatteryState::Discharging => "discharging",
+            BatteryState::NotCharging => "not_charging",
+        };
+
+        CONFIG.icons.maps(style).battery.get(Some(state), percent)
     }
 
     /// # Errors
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
-    pub fn get_tuples() -> Result<Vec<(String, String)>, DaemonError> {
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
         let (state, percent, time) = Self::get()?;
-        let icon = Self::get_icon(&state, percent);
-
-        Ok(vec![
-            ("state".to_string(), BAT_STATE_STRINGS[state as usize].to_string()),
-            ("percent".to_string(), percent.to_string()),
-            ("time".to_string(), time),
-            ("icon".to_string(), format!("{icon}{ICON_EXT}")),
-        ])
+        let icon = Self::get_icon(IconStyle::Name, &state, percent);
+        let glyph = Self::get_icon(IconStyle::Glyph, &state, percent);
+        let state_string = BAT_STATE_STRINGS[state as usize];
+
+        Ok([
+            vec![
+                // The state is matched on by scripts and icon maps, so only the label is translated
+                ("state".to_string(), state_string.into()),
+                ("state_label".to_string(), translate(state_string).into()),
+                ("percent".to_string(), percent.into()),
+                ("time".to_string(), Some(time).filter(|time| !time.is_empty()).into()),
+            ],
+            icon::to_tuples("", &icon, &glyph),
+        ]
+        .concat())
     }
 
     /// # Errors
@@ -172,12 +182,12 @@ impl Battery {
                     item,
                     value: Self::get_time()?,
                 },
-                BatteryItem::Icon => {
+                BatteryItem::Icon(style) => {
                     let (state, percent, _) = Self::get()?;
 
                     DaemonReply::Value {
                         item,
-                        value: Self::get_icon(&state, percent),
+                        value: Self::get_icon(style.unwrap_or(CONFIG.icons.style), &state, percent),
                     }
                 }
                 BatteryItem::All => DaemonReply::Tuples {
@@ -196,7 +206,7 @@ impl Battery {
                     BatteryGetCommands::State => DaemonItem::Battery(BatteryItem::State),
                     BatteryGetCommands::Percent => DaemonItem::Battery(BatteryItem::Percent),
                     BatteryGetCommands::Time => DaemonItem::Battery(BatteryItem::Time),
-                    BatteryGetCommands::Icon => DaemonItem::Battery(BatteryItem::Icon),
+                    BatteryGetCommands::Icon { style } => DaemonItem::Battery(BatteryItem::Icon(*style)),
                 },
                 None => DaemonItem::Battery(BatteryItem::All),
             },
@@ -207,27 +217,22 @@ impl Battery {
     /// Returns an error if the requested value could not be parsed
     pub fn notify(prev_percent: u32) -> Result<(), DaemonError> {
         let (state, current_percent, _) = Self::get()?;
-        let icon = Self::get_icon(&state, current_percent);
+        let icon = Self::get_icon(IconStyle::Name, &state, current_percent);
 
         if current_percent < prev_percent && state == BatteryState::Discharging {
             for &value in BAT_NOTIFY_VALUES.iter().rev() {
                 if current_percent == value {
-                    command::run(
-                        "dunstify",
+                    Notification::new(
+                        &CONFIG.notifications.battery,
+                        "Battery: ",
+                        &icon,
                         &[
-                            "-u",
-                            "-normal",
-                            "-t",
-                            NOTIFICATION_TIMEOUT.to_string().as_str(),
-                            "-i",
-                            icon.to_string().as_str(),
-                            "-r",
-                            NOTIFICATION_ID.to_string().as_str(),
-                            "-h",
-                            format!("int:value:{current_percent}").as_str(),
-                            "Battery: ",
+                            ("percent", current_percent.to_string().as_str()),
+                            ("state", BAT_STATE_STRINGS[BatteryState::Discharging as usize]),
                         ],
-                    )?;
+                    )
+                    .value(current_percent)
+                    .send()?;
                 }
             }
         }
@@ -4,9 +4,12 @@ use serde::{Deserialize, Serialize};
 use crate::{
     cli::parse_bool,
     command,
+    config::CONFIG,
     daemon::{DaemonItem, DaemonMessage, DaemonReply},
     error::DaemonError,
-    ICON_END, ICON_EXT, NOTIFICATION_ID, NOTIFICATION_TIMEOUT,
+    icon::{self, on_off, IconStyle},
+    notification::Notification,
+    tuples::Tuples,
 };
 
 #[derive(Subcommand)]
@@ -14,7 +17,10 @@ pub enum BluetoothGetCommands {
     #[command(alias = "s")]
     State,
     #[command(alias = "i")]
-    Icon,
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -35,7 +41,7 @@ pub enum BluetoothUpdateCommands {
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub enum BluetoothItem {
     State,
-    Icon,
+    Icon(Option<IconStyle>),
     All,
 }
 
@@ -77,21 +83,23 @@ impl Bluetooth {
     }
 
     #[must_use]
-    pub fn get_icon(state: bool) -> String {
-        format!("bluetooth-{}{ICON_END}", if state { "active" } else { "disabled" })
+    pub fn get_icon(style: IconStyle, state: bool) -> String {
+        CONFIG.icons.maps(style).bluetooth.get(Some(on_off(state)), 0)
     }
 
     /// # Errors
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
-    pub fn get_tuples() -> Result<Vec<(String, String)>, DaemonError> {
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
         let state = Self::get_state()?;
-        let icon = Self::get_icon(state);
-
-        Ok(vec![
-            ("state".to_string(), state.to_string()),
-            ("icon".to_string(), format!("{icon}{ICON_EXT}")),
-        ])
+        let icon = Self::get_icon(IconStyle::Name, state);
+        let glyph = Self::get_icon(IconStyle::Glyph, state);
+
+        Ok([
+            vec![("state".to_string(), state.into())],
+            icon::to_tuples("", &icon, &glyph),
+        ]
+        .concat())
     }
 
     /// # Errors
@@ -110,13 +118,19 @@ impl Bluetooth {
             }
 
             let new_state = Self::get_state()?;
+            let changed = prev_state != new_state;
 
-            if prev_state != new_state {
+            if changed {
                 // Do a notification
                 Self::notify()?;
             }
 
-            DaemonReply::Value { item, value }
+            DaemonReply::Set {
+                item,
+                tuples: Self::get_tuples()?,
+                changed,
+                clamped: false,
+            }
         } else {
             // Get value
             match bluetooth_item {
@@ -124,12 +138,12 @@ impl Bluetooth {
                     item,
                     value: Self::get_state()?.to_string(),
                 },
-                BluetoothItem::Icon => {
+                BluetoothItem::Icon(style) => {
                     let state = Self::get_state()?;
 
                     DaemonReply::Value {
                         item,
-                        value: Self::get_icon(state),
+                        value: Self::get_icon(style.unwrap_or(CONFIG.icons.style), state),
                     }
                 }
                 BluetoothItem::All => DaemonReply::Tuples {
@@ -146,7 +160,7 @@ impl Bluetooth {
             item: match commands {
                 Some(commands) => match commands {
                     BluetoothGetCommands::State => DaemonItem::Bluetooth(BluetoothItem::State),
-                    BluetoothGetCommands::Icon => DaemonItem::Bluetooth(BluetoothItem::Icon),
+                    BluetoothGetCommands::Icon { style } => DaemonItem::Bluetooth(BluetoothItem::Icon(*style)),
                 },
                 None => DaemonItem::Bluetooth(BluetoothItem::All),
             },
@@ -174,26 +188,19 @@ impl Bluetooth {
 
     /// # Errors
     /// Returns an error if the requested value could not be parsed
+    // ``{state}`` in the title is a notification placeholder, not a format argument
+    #[allow(clippy::literal_string_with_formatting_args)]
     pub fn notify() -> Result<(), DaemonError> {
         let state = Self::get_state()?;
 
-        let icon = Self::get_icon(state);
-
-        command::run(
-            "dunstify",
-            &[
-                "-u",
-                "normal",
-                "-r",
-                format!("{NOTIFICATION_ID}").as_str(),
-                "-i",
-                icon.trim().to_string().as_str(),
-                "-t",
-                format!("{NOTIFICATION_TIMEOUT}").as_str(),
-                format!("Bluetooth: {}", if state { "on" } else { "off" }).as_str(),
-            ],
-        )?;
+        let icon = Self::get_icon(IconStyle::Name, state);
 
-        Ok(())
+        Notification::new(
+            &CONFIG.notifications.bluetooth,
+            "Bluetooth: {state}",
+            &icon,
+            &[("state", if state { "on" } else { "off" })],
+        )
+        .send()
     }
 }
@@ -1,18 +1,52 @@
-use std::cmp;
+use std::sync::LazyLock;
 
 use clap::Subcommand;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::{
+    cli::parse_duration,
     command,
+    config::CONFIG,
     daemon::{DaemonItem, DaemonMessage, DaemonReply},
     error::DaemonError,
-    ICON_END, ICON_EXT, NOTIFICATION_ID, NOTIFICATION_TIMEOUT,
+    icon::{self, IconStyle},
+    listener::ClientMessage,
+    notification::Notification,
+    tuples::{TupleValue, Tuples},
 };
 
 pub const MONITOR_ID: &str = "nvidia_wmi_ec_backlight";
 pub const KEYBOARD_ID: &str = "asus::kbd_backlight";
 
+// How often the keyboard backlight is checked for changes made by the firmware
+const HW_CHANGED_POLLING_RATE: u64 = 250;
+
+// The VCP feature code for brightness, as used by ``ddcutil``
+const DDC_BRIGHTNESS_CODE: &str = "10";
+
+static DEVICES: LazyLock<Vec<BrightnessDevice>> = LazyLock::new(|| {
+    Brightness::discover().unwrap_or_else(|e| {
+        eprintln!("Could not discover brightness devices:\n\t{e}");
+
+        Vec::new()
+    })
+});
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum BrightnessBackend {
+    Brightnessctl,
+    Ddcutil { display: u32 },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BrightnessDevice {
+    pub name: String,
+    /// ``backlight`` or ``leds`` for brightnessctl devices, ``ddc`` for external monitors
+    pub class: String,
+    pub backend: BrightnessBackend,
+}
+
 #[derive(Subcommand)]
 pub enum BrightnessGetCommands {
     #[command(alias = "mon", alias = "m")]
@@ -20,7 +54,25 @@ pub enum BrightnessGetCommands {
     #[command(alias = "key", alias = "k")]
     Keyboard,
     #[command(alias = "i")]
-    Icon,
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
+    #[command(alias = "monitor-i", alias = "mi")]
+    MonitorIcon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
+    #[command(alias = "keyboard-i", alias = "ki")]
+    KeyboardIcon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
+    #[command(alias = "dev", alias = "d")]
+    Device { name: String },
+    /// List every device along with its percentage
+    #[command(alias = "devs")]
+    Devices,
 }
 
 #[derive(Subcommand)]
@@ -29,11 +81,26 @@ pub enum BrightnessSetCommands {
     Monitor {
         #[arg(allow_hyphen_values = true)]
         value: String,
+        /// Change the brightness gradually over this duration (e.g. 300ms, 2s)
+        #[arg(long, value_parser = parse_duration)]
+        fade: Option<u64>,
     },
     #[command(alias = "key", alias = "k")]
     Keyboard {
         #[arg(allow_hyphen_values = true)]
         value: String,
+        /// Change the brightness gradually over this duration (e.g. 300ms, 2s)
+        #[arg(long, value_parser = parse_duration)]
+        fade: Option<u64>,
+    },
+    #[command(alias = "dev", alias = "d")]
+    Device {
+        name: String,
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+        /// Change the brightness gradually over this duration (e.g. 300ms, 2s)
+        #[arg(long, value_parser = parse_duration)]
+        fade: Option<u64>,
     },
 }
 
@@ -49,116 +116,320 @@ pub enum BrightnessUpdateCommands {
 pub enum BrightnessItem {
     Monitor,
     Keyboard,
-    Icon,
+    Icon(Option<IconStyle>),
+    MonitorIcon(Option<IconStyle>),
+    KeyboardIcon(Option<IconStyle>),
+    Device(String),
+    Devices,
     All,
 }
 
 pub struct Brightness;
 
 impl Brightness {
+    /// Lists every backlight and LED device, and external monitors when ``ddcutil`` is enabled
+    ///
+    /// # Errors
+    /// Returns an error if ``brightnessctl`` cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn discover() -> Result<Vec<BrightnessDevice>, DaemonError> {
+        // Each line looks like `intel_backlight,backlight,400,42%,937`
+        let output = command::run("brightnessctl", &["-l", "-m"])?;
+
+        let mut devices = output
+            .lines()
+            .map(|line| {
+                let mut line_split = line.split(',');
+
+                match (line_split.next(), line_split.next()) {
+                    (Some(name), Some(class)) => Ok(BrightnessDevice {
+                        name: name.to_string(),
+                        class: class.to_string(),
+                        backend: BrightnessBackend::Brightnessctl,
+                    }),
+                    _ => Err(DaemonError::ParseError(line.to_string())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // External monitors are optional, so the backlight and LED devices are kept when ddcutil fails
+        if CONFIG.brightness.ddcutil {
+            match Self::discover_ddc() {
+                Ok(ddc_devices) => devices.extend(ddc_devices),
+                Err(e) => eprintln!("Could not discover ddcutil monitors:\n\t{e}"),
+            }
+        }
+
+        Ok(devices)
+    }
+
+    fn discover_ddc() -> Result<Vec<BrightnessDevice>, DaemonError> {
+        // Each display starts with a line like `Display 1`
+        let output = command::run("ddcutil", &["detect", "--brief"])?;
+
+        output
+            .lines()
+            .filter_map(|line| line.strip_prefix("Display "))
+            .map(|display| {
+                let display = display.trim().parse::<u32>()?;
+
+                Ok(BrightnessDevice {
+                    name: format!("ddc-{display}"),
+                    class: "ddc".to_string(),
+                    backend: BrightnessBackend::Ddcutil { display },
+                })
+            })
+            .collect()
+    }
+
+    /// Finds a discovered device by its name, or by a friendly name from the config
+    ///
+    /// # Errors
+    /// Returns an error if no device has the given name
+    pub fn find_device(name: &str) -> Result<&'static BrightnessDevice, DaemonError> {
+        let device_name = CONFIG.brightness.names.get(name).map_or(name, String::as_str);
+
+        DEVICES
+            .iter()
+            .find(|device| device.name == device_name)
+            .ok_or_else(|| DaemonError::DeviceNotFound(name.to_string()))
+    }
+
+    /// # Errors
+    /// Returns an error if a device in the monitor group cannot be found
+    pub fn get_monitor_group() -> Result<Vec<&'static BrightnessDevice>, DaemonError> {
+        CONFIG
+            .brightness
+            .monitor
+            .iter()
+            .map(|name| Self::find_device(name))
+            .collect()
+    }
+
+    fn get_monitor_device() -> Result<&'static BrightnessDevice, DaemonError> {
+        // The first device in the group is the one which is reported
+        Self::get_monitor_group()?
+            .first()
+            .copied()
+            .ok_or_else(|| DaemonError::DeviceNotFound("monitor".to_string()))
+    }
+
+    fn get_keyboard_device() -> Result<&'static BrightnessDevice, DaemonError> {
+        Self::find_device(&CONFIG.brightness.keyboard)
+    }
+
     /// # Errors
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
-    fn get(device_id: &str) -> Result<f32, DaemonError> {
-        let output = command::run("brightnessctl", &["-m", "-d", device_id, "i"])?;
+    fn get_raw(device: &BrightnessDevice) -> Result<(u32, u32), DaemonError> {
+        let output = match device.backend {
+            BrightnessBackend::Brightnessctl => {
+                command::run("brightnessctl", &["-m", "-d", device.name.as_str(), "i"])?
+            }
+            BrightnessBackend::Ddcutil { display } => command::run(
+                "ddcutil",
+                &[
+                    "-d",
+                    display.to_string().as_str(),
+                    "getvcp",
+                    DDC_BRIGHTNESS_CODE,
+                    "--brief",
+                ],
+            )?,
+        };
 
-        // Split the output by commas
-        let output_split = output.split(',').map(ToString::to_string).collect::<Vec<_>>();
+        // Split the output, by commas for brightnessctl or `VCP 10 C 50 100` for ddcutil
+        let output_split = match device.backend {
+            BrightnessBackend::Brightnessctl => output.split(',').map(ToString::to_string).collect::<Vec<_>>(),
+            BrightnessBackend::Ddcutil { .. } => output.split_whitespace().map(ToString::to_string).collect::<Vec<_>>(),
+        };
 
         // Get the current and maximum brightness values
-        let current_brightness = output_split.get(2);
-        let max_brightness = output_split.get(4);
-
-        // Parse the values into integers, then get the floating point percentage
-        Ok(
-            if let (Some(current_brightness), Some(max_brightness)) = (current_brightness, max_brightness) {
-                let current_value = f64::from(current_brightness.parse::<u32>()?);
-                let max_value = f64::from(max_brightness.parse::<u32>()?);
-
-                ((current_value / max_value) * 100.) as f32
-            } else {
-                return Err(DaemonError::ParseError(output));
-            },
-        )
+        let (current_brightness, max_brightness) = match device.backend {
+            BrightnessBackend::Brightnessctl => (output_split.get(2), output_split.get(4)),
+            BrightnessBackend::Ddcutil { .. } => (output_split.get(3), output_split.get(4)),
+        };
+
+        // Parse the values into integers
+        if let (Some(current_brightness), Some(max_brightness)) = (current_brightness, max_brightness) {
+            Ok((current_brightness.parse::<u32>()?, max_brightness.parse::<u32>()?))
+        } else {
+            Err(DaemonError::ParseError(output))
+        }
+    }
+
+    fn set_raw(device: &BrightnessDevice, value: u32) -> Result<(), DaemonError> {
+        match device.backend {
+            BrightnessBackend::Brightnessctl => command::run(
+                "brightnessctl",
+                &["-d", device.name.as_str(), "s", value.to_string().as_str()],
+            )?,
+            BrightnessBackend::Ddcutil { display } => command::run(
+                "ddcutil",
+                &[
+                    "-d",
+                    display.to_string().as_str(),
+                    "setvcp",
+                    DDC_BRIGHTNESS_CODE,
+                    value.to_string().as_str(),
+                ],
+            )?,
+        };
+
+        Ok(())
+    }
+
+    /// # Errors
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    fn get(device: &BrightnessDevice) -> Result<u32, DaemonError> {
+        let (current_value, max_value) = Self::get_raw(device)?;
+
+        // Get the floating point percentage of the device, then map it back onto the curve
+        let device_percent = (f64::from(current_value) / f64::from(max_value)) * 100.;
+
+        Ok(CONFIG.brightness.curve.device_to_linear_percent(device_percent))
     }
 
     /// # Errors
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
-    pub fn get_monitor() -> Result<f32, DaemonError> {
-        Self::get(MONITOR_ID)
+    pub fn get_monitor() -> Result<u32, DaemonError> {
+        Self::get(Self::get_monitor_device()?)
     }
 
     /// # Errors
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
-    pub fn get_keyboard() -> Result<f32, DaemonError> {
-        Self::get(KEYBOARD_ID)
+    pub fn get_keyboard() -> Result<u32, DaemonError> {
+        Self::get(Self::get_keyboard_device()?)
+    }
+
+    /// # Errors
+    /// Returns an error if no device has the given name
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn get_device(name: &str) -> Result<u32, DaemonError> {
+        Self::get(Self::find_device(name)?)
     }
 
     #[must_use]
-    pub fn get_icon(device_id: &str, percent: f32) -> String {
-        let percent = percent as u32;
-
-        if device_id == MONITOR_ID {
-            format!(
-                "display-brightness-{}{ICON_END}",
-                match percent {
-                    0 => "off",
-                    1..=33 => "low",
-                    34..=67 => "medium",
-                    68.. => "high",
-                }
-            )
+    pub fn get_icon(style: IconStyle, device: &BrightnessDevice, percent: u32) -> String {
+        if device.class == "leds" {
+            CONFIG.icons.maps(style).keyboard.get(None, percent)
         } else {
-            let strength = match percent {
-                0 => "off",
-                1..=33 => "medium",
-                34..=67 => "",
-                68.. => "high",
-            };
-
-            format!(
-                "keyboard-brightness{}{ICON_END}",
-                if strength.is_empty() {
-                    String::new()
-                } else {
-                    format!("-{strength}")
-                }
-            )
+            CONFIG.icons.maps(style).monitor.get(None, percent)
         }
     }
 
-    fn set(device_id: &str, percent_string: &str) -> Result<(), DaemonError> {
+    // The percentage which the percent string gives, and whether it had to be limited to between 0 and 100
+    fn apply_percent_string(current_percent: u32, percent_string: &str) -> Result<(f64, bool), DaemonError> {
         // Change the percentage based on the delta percentage
         let percent = if percent_string.starts_with('+') || percent_string.starts_with('-') {
             let delta_percent = percent_string.parse::<f64>()?;
-            let current_percent = f64::from(Self::get(device_id)?);
 
             // Depending on the first char, add or subtract the percentage
-            (current_percent + delta_percent).clamp(0.0, 100.0)
+            f64::from(current_percent) + delta_percent
         } else {
             percent_string.parse::<f64>()?
         };
 
-        // Set the percentage
-        command::run("brightnessctl", &["-d", device_id, "s", format!("{percent}%").as_str()])?;
+        let limited_percent = percent.clamp(0.0, 100.0);
+
+        Ok((limited_percent, (limited_percent - percent).abs() > f64::EPSILON))
+    }
+
+    fn set(device: &BrightnessDevice, percent_string: &str) -> Result<(), DaemonError> {
+        let (current_value, max_value) = Self::get_raw(device)?;
+        let current_percent = CONFIG
+            .brightness
+            .curve
+            .device_to_linear_percent((f64::from(current_value) / f64::from(max_value)) * 100.);
+
+        let (percent, _) = Self::apply_percent_string(current_percent, percent_string)?;
+
+        // Convert to a raw device value, rounding so that getting the percentage back gives the same value
+        let device_percent = CONFIG.brightness.curve.linear_to_device(percent);
+        let value = ((device_percent / 100.) * f64::from(max_value)).round() as u32;
+
+        Self::set_raw(device, value)
+    }
+
+    /// # Errors
+    /// Returns an error if the device for the item cannot be found
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn get_item(brightness_item: &BrightnessItem) -> Result<u32, DaemonError> {
+        match brightness_item {
+            BrightnessItem::Keyboard | BrightnessItem::KeyboardIcon(_) => Self::get_keyboard(),
+            BrightnessItem::Device(name) => Self::get_device(name),
+            _ => Self::get_monitor(),
+        }
+    }
+
+    // The raw values of the item's devices, which change even when the rounded percentage doesn't
+    fn get_item_raw(brightness_item: &BrightnessItem) -> Result<Vec<u32>, DaemonError> {
+        let devices = match brightness_item {
+            BrightnessItem::Keyboard | BrightnessItem::KeyboardIcon(_) => vec![Self::get_keyboard_device()?],
+            BrightnessItem::Device(name) => vec![Self::find_device(name)?],
+            _ => Self::get_monitor_group()?,
+        };
+
+        devices
+            .into_iter()
+            .map(|device| Self::get_raw(device).map(|(value, _)| value))
+            .collect()
+    }
+
+    /// Works out the percentage which setting ``percent_string`` on the item would result in, and whether it is clamped
+    ///
+    /// # Errors
+    /// Returns an error if the current percentage cannot be found
+    /// Returns an error if the percentage string cannot be parsed
+    pub fn get_target_percent(
+        brightness_item: &BrightnessItem,
+        percent_string: &str,
+    ) -> Result<(u32, bool), DaemonError> {
+        let (percent, clamped) = Self::apply_percent_string(Self::get_item(brightness_item)?, percent_string)?;
+
+        Ok((percent.round() as u32, clamped))
+    }
+
+    /// Sets the item without showing a notification
+    ///
+    /// # Errors
+    /// Returns an error if the device for the item cannot be found
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn set_item_quiet(brightness_item: &BrightnessItem, percent: &str) -> Result<(), DaemonError> {
+        match brightness_item {
+            BrightnessItem::Monitor => {
+                // Every device in the group is changed, relative changes are relative to each device
+                for device in Self::get_monitor_group()? {
+                    Self::set(device, percent)?;
+                }
+            }
+            BrightnessItem::Keyboard => Self::set(Self::get_keyboard_device()?, percent)?,
+            BrightnessItem::Device(name) => Self::set(Self::find_device(name)?, percent)?,
+            _ => {}
+        }
 
         Ok(())
     }
 
     /// # Errors
+    /// Returns an error if a device in the monitor group cannot be found
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
     pub fn set_monitor(percent: &str) -> Result<(), DaemonError> {
         let prev_monitor = Self::get_monitor()?;
 
-        Self::set(MONITOR_ID, percent)?;
+        Self::set_item_quiet(&BrightnessItem::Monitor, percent)?;
 
         let new_monitor = Self::get_monitor()?;
 
-        if prev_monitor.partial_cmp(&new_monitor) != Some(cmp::Ordering::Equal) {
-            Self::notify(MONITOR_ID)?;
+        if prev_monitor != new_monitor {
+            Self::notify_monitor()?;
         }
 
         Ok(())
@@ -170,12 +441,30 @@ impl Brightness {
     pub fn set_keyboard(percent: &str) -> Result<(), DaemonError> {
         let prev_keyboard = Self::get_keyboard()?;
 
-        Self::set(KEYBOARD_ID, percent)?;
+        Self::set_item_quiet(&BrightnessItem::Keyboard, percent)?;
 
         let new_keyboard = Self::get_keyboard()?;
 
-        if prev_keyboard.partial_cmp(&new_keyboard) != Some(cmp::Ordering::Equal) {
-            Self::notify(KEYBOARD_ID)?;
+        if prev_keyboard != new_keyboard {
+            Self::notify_keyboard()?;
+        }
+
+        Ok(())
+    }
+
+    /// # Errors
+    /// Returns an error if no device has the given name
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn set_device(name: &str, percent: &str) -> Result<(), DaemonError> {
+        let prev_percent = Self::get_device(name)?;
+
+        Self::set_item_quiet(&BrightnessItem::Device(name.to_string()), percent)?;
+
+        let new_percent = Self::get_device(name)?;
+
+        if prev_percent != new_percent {
+            Self::notify_device(name)?;
         }
 
         Ok(())
@@ -183,24 +472,108 @@ impl Brightness {
 
     /// # Errors
     /// Returns an error if the requested value could not be parsed
-    pub fn get_tuples() -> Result<Vec<(String, String)>, DaemonError> {
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
+        let monitor_device = Self::get_monitor_device()?;
         let monitor_percent = Self::get_monitor()?;
-        let icon = Self::get_icon(MONITOR_ID, monitor_percent);
+        let icon = Self::get_icon(IconStyle::Name, monitor_device, monitor_percent);
+        let glyph = Self::get_icon(IconStyle::Glyph, monitor_device, monitor_percent);
+
+        Ok([
+            vec![("monitor_percent".to_string(), monitor_percent.into())],
+            icon::to_tuples("", &icon, &glyph),
+            Self::get_keyboard_tuples()?,
+        ]
+        .concat())
+    }
+
+    // A machine without a keyboard backlight still has its keys, which are null
+    fn get_keyboard_tuples() -> Result<Tuples, DaemonError> {
+        let keyboard_device = match Self::get_keyboard_device() {
+            Ok(keyboard_device) => keyboard_device,
+            Err(DaemonError::DeviceNotFound(_)) => {
+                return Ok([
+                    vec![("keyboard_percent".to_string(), TupleValue::Null)],
+                    icon::to_tuples("keyboard_", "", ""),
+                ]
+                .concat()
+                .into_iter()
+                .map(|(key, _)| (key, TupleValue::Null))
+                .collect());
+            }
+            Err(e) => return Err(e),
+        };
+
+        let keyboard_percent = Self::get(keyboard_device)?;
+        let keyboard_icon = Self::get_icon(IconStyle::Name, keyboard_device, keyboard_percent);
+        let keyboard_glyph = Self::get_icon(IconStyle::Glyph, keyboard_device, keyboard_percent);
 
-        Ok(vec![
-            ("monitor_percent".to_string(), (monitor_percent as u32).to_string()),
-            ("icon".to_string(), format!("{icon}{ICON_EXT}")),
-        ])
+        Ok([
+            vec![("keyboard_percent".to_string(), keyboard_percent.into())],
+            icon::to_tuples("keyboard_", &keyboard_icon, &keyboard_glyph),
+        ]
+        .concat())
+    }
+
+    /// # Errors
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn get_device_tuples() -> Result<Tuples, DaemonError> {
+        DEVICES
+            .iter()
+            .map(|device| Ok((device.name.clone(), Self::get(device)?.into())))
+            .collect()
+    }
+
+    fn get_keyboard_hw_changed() -> Option<String> {
+        let keyboard = Self::get_keyboard_device().ok()?;
+
+        // The attribute can't be read until the firmware has changed the brightness at least once
+        std::fs::read_to_string(format!("/sys/class/leds/{}/brightness_hw_changed", keyboard.name))
+            .ok()
+            .map(|value| value.trim().to_string())
+    }
+
+    /// Watches ``brightness_hw_changed`` for keyboard backlight changes made by the firmware hotkey
+    ///
+    /// # Errors
+    /// Returns an error if the notification cannot be shown
+    /// Returns an error if the update cannot be sent to the client handler
+    pub async fn monitor_keyboard(clients_tx: mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+        let mut prev_hw_changed = Self::get_keyboard_hw_changed();
+
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_millis(HW_CHANGED_POLLING_RATE)).await;
+
+            let hw_changed = Self::get_keyboard_hw_changed();
+
+            if hw_changed.is_some() && hw_changed != prev_hw_changed {
+                Self::notify_keyboard()?;
+
+                clients_tx.send(ClientMessage::UpdateBrightness)?;
+            }
+
+            prev_hw_changed = hw_changed;
+        }
     }
 
     #[must_use]
-    pub const fn match_get_commands(commands: &Option<BrightnessGetCommands>) -> DaemonMessage {
+    pub fn match_get_commands(commands: &Option<BrightnessGetCommands>) -> DaemonMessage {
         DaemonMessage::Get {
             item: match commands {
                 Some(commands) => match commands {
                     BrightnessGetCommands::Monitor => DaemonItem::Brightness(BrightnessItem::Monitor),
                     BrightnessGetCommands::Keyboard => DaemonItem::Brightness(BrightnessItem::Keyboard),
-                    BrightnessGetCommands::Icon => DaemonItem::Brightness(BrightnessItem::Icon),
+                    BrightnessGetCommands::Icon { style } => DaemonItem::Brightness(BrightnessItem::Icon(*style)),
+                    BrightnessGetCommands::MonitorIcon { style } => {
+                        DaemonItem::Brightness(BrightnessItem::MonitorIcon(*style))
+                    }
+                    BrightnessGetCommands::KeyboardIcon { style } => {
+                        DaemonItem::Brightness(BrightnessItem::KeyboardIcon(*style))
+                    }
+                    BrightnessGetCommands::Device { name } => {
+                        DaemonItem::Brightness(BrightnessItem::Device(name.clone()))
+                    }
+                    BrightnessGetCommands::Devices => DaemonItem::Brightness(BrightnessItem::Devices),
                 },
                 None => DaemonItem::Brightness(BrightnessItem::All),
             },
@@ -209,15 +582,17 @@ impl Brightness {
 
     #[must_use]
     pub fn match_set_commands(commands: BrightnessSetCommands) -> DaemonMessage {
-        match commands {
-            BrightnessSetCommands::Monitor { value } => DaemonMessage::Set {
-                item: DaemonItem::Brightness(BrightnessItem::Monitor),
-                value,
-            },
-            BrightnessSetCommands::Keyboard { value } => DaemonMessage::Set {
-                item: DaemonItem::Brightness(BrightnessItem::Keyboard),
-                value,
-            },
+        let (brightness_item, value, fade) = match commands {
+            BrightnessSetCommands::Monitor { value, fade } => (BrightnessItem::Monitor, value, fade),
+            BrightnessSetCommands::Keyboard { value, fade } => (BrightnessItem::Keyboard, value, fade),
+            BrightnessSetCommands::Device { name, value, fade } => (BrightnessItem::Device(name), value, fade),
+        };
+
+        let item = DaemonItem::Brightness(brightness_item);
+
+        match fade {
+            Some(duration) => DaemonMessage::Fade { item, value, duration },
+            None => DaemonMessage::Set { item, value },
         }
     }
 
@@ -241,16 +616,31 @@ impl Brightness {
         value: Option<String>,
     ) -> Result<DaemonReply, DaemonError> {
         Ok(if let Some(value) = value {
+            let prev_raw = Self::get_item_raw(brightness_item)?;
+            let (_, clamped) = Self::apply_percent_string(Self::get_item(brightness_item)?, value.as_str())?;
+
             // Set value
             match brightness_item {
                 BrightnessItem::Monitor => Self::set_monitor(value.as_str())?,
                 BrightnessItem::Keyboard => Self::set_keyboard(value.as_str())?,
+                BrightnessItem::Device(name) => Self::set_device(name, value.as_str())?,
                 _ => {}
             }
 
             // Notifications are done in the set_* functions
 
-            DaemonReply::Value { item, value }
+            let changed = prev_raw != Self::get_item_raw(brightness_item)?;
+
+            DaemonReply::Set {
+                item,
+                tuples: if matches!(brightness_item, BrightnessItem::Device(_)) {
+                    Self::get_device_tuples()?
+                } else {
+                    Self::get_tuples()?
+                },
+                changed,
+                clamped,
+            }
         } else {
             // Get value
             match brightness_item {
@@ -262,14 +652,38 @@ impl Brightness {
                     item,
                     value: Self::get_keyboard()?.to_string(),
                 },
-                BrightnessItem::Icon => {
+                BrightnessItem::Icon(style) | BrightnessItem::MonitorIcon(style) => {
                     let percent = Self::get_monitor()?;
 
                     DaemonReply::Value {
                         item,
-                        value: Self::get_icon(MONITOR_ID, percent),
+                        value: Self::get_icon(
+                            style.unwrap_or(CONFIG.icons.style),
+                            Self::get_monitor_device()?,
+                            percent,
+                        ),
+                    }
+                }
+                BrightnessItem::KeyboardIcon(style) => {
+                    let percent = Self::get_keyboard()?;
+
+                    DaemonReply::Value {
+                        item,
+                        value: Self::get_icon(
+                            style.unwrap_or(CONFIG.icons.style),
+                            Self::get_keyboard_device()?,
+                            percent,
+                        ),
                     }
                 }
+                BrightnessItem::Device(name) => DaemonReply::Value {
+                    item,
+                    value: Self::get_device(name)?.to_string(),
+                },
+                BrightnessItem::Devices => DaemonReply::Tuples {
+                    item,
+                    tuples: Self::get_device_tuples()?,
+                },
                 BrightnessItem::All => DaemonReply::Tuples {
                     item,
                     tuples: Self::get_tuples()?,
@@ -279,29 +693,82 @@ impl Brightness {
     }
 
     /// # Errors
+    /// Returns an error if the device for the item cannot be found
     /// Returns an error if the requested value could not be parsed
-    pub fn notify(device_id: &str) -> Result<(), DaemonError> {
-        let percent = Self::get(device_id)?;
-
-        let icon = Self::get_icon(device_id, percent);
-
-        command::run(
-            "dunstify",
-            &[
-                "-u",
-                "normal",
-                "-r",
-                format!("{NOTIFICATION_ID}").as_str(),
-                "-i",
-                icon.as_str(),
-                "-t",
-                format!("{NOTIFICATION_TIMEOUT}").as_str(),
-                "-h",
-                format!("int:value:{percent}").as_str(),
-                format!("{}: ", if device_id == MONITOR_ID { "Monitor" } else { "Keyboard" }).as_str(),
-            ],
-        )?;
+    pub fn notify_item(brightness_item: &BrightnessItem) -> Result<(), DaemonError> {
+        match brightness_item {
+            BrightnessItem::Keyboard | BrightnessItem::KeyboardIcon(_) => Self::notify_keyboard(),
+            BrightnessItem::Device(name) => Self::notify_device(name),
+            _ => Self::notify_monitor(),
+        }
+    }
 
-        Ok(())
+    /// # Errors
+    /// Returns an error if the requested value could not be parsed
+    pub fn notify_monitor() -> Result<(), DaemonError> {
+        Self::notify(Self::get_monitor_device()?, "Monitor")
+    }
+
+    /// # Errors
+    /// Returns an error if the requested value could not be parsed
+    pub fn notify_keyboard() -> Result<(), DaemonError> {
+        Self::notify(Self::get_keyboard_device()?, "Keyboard")
+    }
+
+    /// # Errors
+    /// Returns an error if no device has the given name
+    /// Returns an error if the requested value could not be parsed
+    pub fn notify_device(name: &str) -> Result<(), DaemonError> {
+        Self::notify(Self::find_device(name)?, name)
+    }
+
+    // The title's ``{device}`` is filled in by the notification template
+    #[allow(clippy::literal_string_with_formatting_args)]
+    fn notify(device: &BrightnessDevice, name: &str) -> Result<(), DaemonError> {
+        let percent = Self::get(device)?;
+
+        let icon = Self::get_icon(IconStyle::Name, device, percent);
+
+        Notification::new(
+            &CONFIG.notifications.brightness,
+            "{device}: ",
+            &icon,
+            &[("percent", percent.to_string().as_str()), ("device", name)],
+        )
+        .value(percent)
+        .send()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(current_percent: u32, percent_string: &str) -> Option<(u32, bool)> {
+        Brightness::apply_percent_string(current_percent, percent_string)
+            .ok()
+            .map(|(percent, clamped)| (percent.round() as u32, clamped))
+    }
+
+    #[test]
+    fn applies_percent_string() {
+        assert_eq!(apply(40, "60"), Some((60, false)));
+        assert_eq!(apply(40, "+5"), Some((45, false)));
+        assert_eq!(apply(40, "-5"), Some((35, false)));
+        assert_eq!(apply(40, "+2.5"), Some((43, false)));
+    }
+
+    #[test]
+    fn clamps_percent_string() {
+        assert_eq!(apply(98, "+5"), Some((100, true)));
+        assert_eq!(apply(3, "-5"), Some((0, true)));
+        assert_eq!(apply(40, "120"), Some((100, true)));
+        assert_eq!(apply(95, "+5"), Some((100, false)));
+    }
+
+    #[test]
+    fn rejects_invalid_percent_string() {
+        assert_eq!(apply(40, "bright"), None);
+        assert_eq!(apply(40, "+abc"), None);
     }
 }
@@ -4,10 +4,13 @@ use crate::{
     battery::{Battery, BatteryGetCommands},
     bluetooth::{Bluetooth, BluetoothGetCommands, BluetoothSetCommands, BluetoothUpdateCommands},
     brightness::{Brightness, BrightnessGetCommands, BrightnessSetCommands, BrightnessUpdateCommands},
-    daemon::{do_daemon, send_daemon_messaage, DaemonItem, DaemonMessage},
+    daemon::{do_daemon, send_daemon_messaage, DaemonItem, DaemonMessage, DaemonReply},
+    dnd::{Dnd, DndGetCommands, DndSetCommands, DndUpdateCommands},
     error::DaemonError,
     fan_profile::{FanProfile, FanProfileGetCommands, FanProfileSetCommands, FanProfileUpdateCommands},
+    json,
     listener::listen,
+    output::OutputMode,
     ram::{Ram, RamGetCommands},
     volume::{Volume, VolumeGetCommands, VolumeSetCommands, VolumeUpdateCommands},
 };
@@ -25,11 +28,29 @@ pub enum CliCommands {
     Get {
         #[command(subcommand)]
         commands: Option<GetCommands>,
+        /// Print the reply through a template, such as '{volume.icon} {volume.percent}%'
+        #[arg(long, global = true)]
+        format: Option<String>,
+        /// Print only the value, or the JSON of the tuples
+        #[arg(long, global = true)]
+        raw: bool,
+        /// Print the JSON of a Waybar custom module for the module, such as volume
+        #[arg(long, global = true)]
+        waybar: Option<String>,
+        /// Write every value in the JSON as a string, even when the config doesn't
+        #[arg(long, global = true)]
+        string_values: bool,
     },
     #[command(alias = "s")]
     Set {
         #[command(subcommand)]
         commands: SetCommands,
+        /// Print the module's values after the change through a template, with '{changed}' and '{clamped}'
+        #[arg(long, global = true)]
+        format: Option<String>,
+        /// Print the JSON of the module's values after the change, and whether they changed or were clamped
+        #[arg(long, global = true)]
+        raw: bool,
     },
     #[command(alias = "u", alias = "up")]
     Update {
@@ -37,7 +58,20 @@ pub enum CliCommands {
         commands: UpdateCommands,
     },
     #[command(alias = "lis", alias = "l")]
-    Listen,
+    Listen {
+        /// Print each update through a template, such as '{volume.icon} {volume.percent}%'
+        #[arg(long)]
+        format: Option<String>,
+        /// Print each update as the JSON of a Waybar custom module for the module, such as volume
+        #[arg(long)]
+        waybar: Option<String>,
+        /// Speak the i3bar protocol, with a block for each module and click events read from stdin
+        #[arg(long)]
+        i3bar: bool,
+        /// Write every value in the JSON as a string, even when the config doesn't
+        #[arg(long)]
+        string_values: bool,
+    },
     #[command(alias = "dae", alias = "d")]
     Daemon,
 }
@@ -71,6 +105,11 @@ pub enum SetCommands {
         #[command(subcommand)]
         commands: FanProfileSetCommands,
     },
+    #[command(alias = "notifications")]
+    Dnd {
+        #[command(subcommand)]
+        commands: DndSetCommands,
+    },
 }
 
 // Should be the same as SetCommands, but the subcommands shouldn't take values
@@ -103,6 +142,11 @@ pub enum UpdateCommands {
         #[command(subcommand)]
         commands: FanProfileUpdateCommands,
     },
+    #[command(alias = "notifications")]
+    Dnd {
+        #[command(subcommand)]
+        commands: DndUpdateCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -144,6 +188,11 @@ pub enum GetCommands {
         #[command(subcommand)]
         commands: FanProfileGetCommands,
     },
+    #[command(alias = "notifications")]
+    Dnd {
+        #[command(subcommand)]
+        commands: Option<DndGetCommands>,
+    },
     #[command(alias = "a")]
     All,
 }
@@ -155,8 +204,22 @@ pub enum GetCommands {
 pub async fn match_cli() -> Result<(), DaemonError> {
     let cli = Cli::parse();
 
+    let mut output = OutputMode::default();
+
     let message_to_send = match cli.commands {
-        CliCommands::Get { commands } => {
+        CliCommands::Get {
+            commands,
+            format,
+            raw,
+            waybar,
+            string_values,
+        } => {
+            output = OutputMode::new(format, raw, waybar, false);
+
+            if string_values {
+                json::set_string_values();
+            }
+
             if let Some(commands) = commands {
                 match commands {
                     GetCommands::Volume { commands } => Volume::match_get_commands(&commands),
@@ -165,26 +228,36 @@ pub async fn match_cli() -> Result<(), DaemonError> {
                     GetCommands::Battery { commands } => Battery::match_get_commands(&commands),
                     GetCommands::Ram { commands } => Ram::match_get_commands(&commands),
                     GetCommands::FanProfile { commands } => FanProfile::match_get_commands(&commands),
+                    GetCommands::Dnd { commands } => Dnd::match_get_commands(&commands),
                     GetCommands::All => DaemonMessage::Get { item: DaemonItem::All },
                 }
             } else {
                 DaemonMessage::Get { item: DaemonItem::All }
             }
         }
-        CliCommands::Set { commands } => match commands {
-            SetCommands::Volume { commands } => Volume::match_set_commands(commands),
-            SetCommands::Brightness { commands } => Brightness::match_set_commands(commands),
-            SetCommands::Bluetooth { commands } => Bluetooth::match_set_commands(&commands),
-            SetCommands::FanProfile { commands } => FanProfile::match_set_commands(commands),
-        },
+        CliCommands::Set { commands, format, raw } => {
+            output = OutputMode::new(format, raw, None, false);
+
+            match_set_commands(commands)
+        }
         CliCommands::Update { commands } => match commands {
             UpdateCommands::Volume { commands } => Volume::match_update_commands(&commands),
             UpdateCommands::Brightness { commands } => Brightness::match_update_commands(&commands),
             UpdateCommands::Bluetooth { commands } => Bluetooth::match_update_commands(&commands),
             UpdateCommands::FanProfile { commands } => FanProfile::match_update_commands(&commands),
+            UpdateCommands::Dnd { commands } => Dnd::match_update_commands(&commands),
         },
-        CliCommands::Listen => {
-            listen().await?;
+        CliCommands::Listen {
+            format,
+            waybar,
+            i3bar,
+            string_values,
+        } => {
+            if string_values {
+                json::set_string_values();
+            }
+
+            listen(&OutputMode::new(format, false, waybar, i3bar)).await?;
 
             return Ok(());
         }
@@ -197,11 +270,48 @@ pub async fn match_cli() -> Result<(), DaemonError> {
     };
 
     let reply = send_daemon_messaage(message_to_send).await?;
-    println!("{reply:?}");
+    let text = output.reply_to_string(&reply)?;
+
+    if matches!(reply, DaemonReply::Error { .. }) {
+        // Waybar shows the error in the bar, otherwise it goes to stderr
+        if matches!(output, OutputMode::Waybar(_)) {
+            println!("{text}");
+        } else {
+            eprintln!("{text}");
+        }
+
+        std::process::exit(1);
+    }
+
+    println!("{text}");
 
     Ok(())
 }
 
+fn match_set_commands(commands: SetCommands) -> DaemonMessage {
+    match commands {
+        SetCommands::Volume { commands } => Volume::match_set_commands(commands),
+        SetCommands::Brightness { commands } => Brightness::match_set_commands(commands),
+        SetCommands::Bluetooth { commands } => Bluetooth::match_set_commands(&commands),
+        SetCommands::FanProfile { commands } => FanProfile::match_set_commands(commands),
+        SetCommands::Dnd { commands } => Dnd::match_set_commands(&commands),
+    }
+}
+
+/// Parses the arguments of a set command, such as ``bluetooth state false``, into the message it would send
+///
+/// # Errors
+/// Returns an error if the arguments are not a valid set command
+pub fn parse_set_message(args: &str) -> Result<DaemonMessage, DaemonError> {
+    let cli = Cli::try_parse_from(["bar_daemon", "set"].into_iter().chain(args.split_whitespace()))
+        .map_err(|e| DaemonError::ParseError(e.to_string()))?;
+
+    match cli.commands {
+        CliCommands::Set { commands, .. } => Ok(match_set_commands(commands)),
+        _ => Err(DaemonError::ParseError(args.to_string())),
+    }
+}
+
 /// # Errors
 /// Returns an error if the bool was not in the correct format
 pub fn parse_bool(s: &str) -> Result<bool, String> {
@@ -211,3 +321,50 @@ pub fn parse_bool(s: &str) -> Result<bool, String> {
         other => Err(format!("Invalid value '{other}' for boolean. Use true/false or 1/0.")),
     }
 }
+
+/// # Errors
+/// Returns an error if the duration was not a number followed by ms, s, m or h
+pub fn parse_duration(s: &str) -> Result<u64, String> {
+    let s = s.trim().to_lowercase();
+
+    // Split into the number and the unit, a missing unit is milliseconds
+    let unit_start = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let (number, unit) = s.split_at(unit_start);
+
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid duration '{s}'. Use a number followed by ms, s, m or h."))?;
+
+    let multiplier = match unit.trim() {
+        "" | "ms" => 1.,
+        "s" => 1000.,
+        "m" | "min" => 60. * 1000.,
+        "h" => 60. * 60. * 1000.,
+        other => return Err(format!("Invalid unit '{other}' for duration. Use ms, s, m or h.")),
+    };
+
+    Ok((number * multiplier).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_units() {
+        assert_eq!(parse_duration("300"), Ok(300));
+        assert_eq!(parse_duration("300ms"), Ok(300));
+        assert_eq!(parse_duration("1.5s"), Ok(1500));
+        assert_eq!(parse_duration("30m"), Ok(1_800_000));
+        assert_eq!(parse_duration("2min"), Ok(120_000));
+        assert_eq!(parse_duration(" 1H "), Ok(3_600_000));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("5 days").is_err());
+        assert!(parse_duration("-5s").is_err());
+    }
+}
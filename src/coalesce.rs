@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        LazyLock, Mutex,
+    },
+};
+
+use crate::{
+    daemon::{DaemonItem, DaemonReply},
+    error::DaemonError,
+};
+
+// Set requests which are waiting for the request before them to finish, keyed by the item which they set
+static QUEUES: LazyLock<Mutex<HashMap<String, Vec<QueuedSet>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// A queued set request, along with every request which was merged into it
+pub struct QueuedSet {
+    pub value: String,
+    // Each merged request waits for its own reply
+    reply_txs: Vec<Sender<DaemonReply>>,
+}
+
+impl QueuedSet {
+    /// Sends the reply to every request which was merged into this one
+    pub fn reply(self, reply: &DaemonReply) {
+        for reply_tx in self.reply_txs {
+            // The request is gone if its client has disconnected
+            let _ = reply_tx.send(reply.clone());
+        }
+    }
+}
+
+fn get_key(item: &DaemonItem) -> String {
+    format!("{item:?}")
+}
+
+/// Queues a set request, merging it into the last queued request for the item when possible
+///
+/// Returns ``None`` if no request for the item is running, in which case the caller should run its own request,
+/// then the queue with ``next``, otherwise returns the receiver for the request's reply
+///
+/// # Errors
+/// Returns an error if the queues mutex cannot be locked
+pub fn push(item: &DaemonItem, value: &str) -> Result<Option<Receiver<DaemonReply>>, DaemonError> {
+    let mut queues = QUEUES.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+    let Some(queue) = queues.get_mut(&get_key(item)) else {
+        // The caller runs this request, and any which are queued while it runs
+        queues.insert(get_key(item), Vec::new());
+
+        return Ok(None);
+    };
+
+    let (reply_tx, reply_rx) = mpsc::channel();
+
+    let merged = queue.last().and_then(|last| merge(&last.value, value));
+
+    match (queue.last_mut(), merged) {
+        (Some(last), Some(merged)) => {
+            last.value = merged;
+            last.reply_txs.push(reply_tx);
+        }
+        _ => queue.push(QueuedSet {
+            value: value.to_string(),
+            reply_txs: vec![reply_tx],
+        }),
+    }
+
+    drop(queues);
+
+    Ok(Some(reply_rx))
+}
+
+/// Takes the next queued request for the item, ``None`` when the queue is empty and no longer running
+///
+/// # Errors
+/// Returns an error if the queues mutex cannot be locked
+pub fn next(item: &DaemonItem) -> Result<Option<QueuedSet>, DaemonError> {
+    let mut queues = QUEUES.lock().map_err(|_| DaemonError::MutexLockError)?;
+    let key = get_key(item);
+
+    let value = match queues.get_mut(&key) {
+        Some(queue) if !queue.is_empty() => Some(queue.remove(0)),
+        _ => {
+            queues.remove(&key);
+
+            None
+        }
+    };
+
+    drop(queues);
+
+    Ok(value)
+}
+
+/// Combines two whole-number values, so that ``+1`` and ``+1`` become ``+2``, and an absolute value replaces the first
+fn merge(first: &str, second: &str) -> Option<String> {
+    let (first_relative, first_number) = parse_number(first)?;
+    let (second_relative, second_number) = parse_number(second)?;
+
+    Some(match (first_relative, second_relative) {
+        (_, false) => second.to_string(),
+        (true, true) => format!("{:+}", first_number + second_number),
+        (false, true) => (first_number + second_number).max(0).to_string(),
+    })
+}
+
+// Whether the value is a change, and its signed number
+fn parse_number(value: &str) -> Option<(bool, i64)> {
+    let relative = value.starts_with('+') || value.starts_with('-');
+    let number = value.trim_start_matches('+').parse::<i64>().ok()?;
+
+    Some((relative, number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_changes() {
+        assert_eq!(merge("+5", "+5").as_deref(), Some("+10"));
+        assert_eq!(merge("+5", "-8").as_deref(), Some("-3"));
+        assert_eq!(merge("-5", "+5").as_deref(), Some("+0"));
+    }
+
+    #[test]
+    fn applies_change_to_value() {
+        assert_eq!(merge("40", "+5").as_deref(), Some("45"));
+        assert_eq!(merge("3", "-5").as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn value_replaces() {
+        assert_eq!(merge("+5", "40").as_deref(), Some("40"));
+        assert_eq!(merge("40", "60").as_deref(), Some("60"));
+    }
+
+    #[test]
+    fn keeps_values_which_are_not_numbers() {
+        assert_eq!(merge("toggle", "+5"), None);
+        assert_eq!(merge("+5", "true"), None);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{collections::HashMap, path::PathBuf, sync::LazyLock};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{
+    brightness::{KEYBOARD_ID, MONITOR_ID},
+    cli::parse_duration,
+    error::DaemonError,
+    i3bar::I3barConfig,
+    icon::{IconMaps, IconStyle, PartialIconMaps},
+    log_linear::Curve,
+    notification::{NotificationAction, NotificationChannel, Notifier, PartialNotificationChannel, Urgency},
+    schedule::{ScheduleSlot, SlotTime},
+    waybar::WaybarConfig,
+    NOTIFICATION_ID,
+};
+
+pub const CONFIG_DIR_NAME: &str = "bar_daemon";
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+pub static CONFIG: LazyLock<Config> = LazyLock::new(|| {
+    Config::load().unwrap_or_else(|e| {
+        eprintln!("Could not load config, using defaults:\n\t{e}");
+
+        Config::default()
+    })
+});
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub volume: VolumeConfig,
+    pub brightness: BrightnessConfig,
+    pub schedule: ScheduleConfig,
+    pub restore: RestoreConfig,
+    pub notifications: NotificationsConfig,
+    pub dnd: DndConfig,
+    pub icons: IconsConfig,
+    pub waybar: WaybarConfig,
+    pub i3bar: I3barConfig,
+    pub json: JsonConfig,
+    /// Language of the translation catalog, such as ``de`` or ``pt_BR``, the system locale is used if not set
+    pub locale: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct VolumeConfig {
+    /// Subscribe to ``pactl`` events so that changes from other tools are picked up
+    pub track_external_changes: bool,
+    /// Show a notification when the volume is changed by another tool
+    pub notify_external_changes: bool,
+    /// Mapping between the percentage shown and the volume given to ``wpctl``
+    pub curve: Curve,
+    /// Highest percent which can be reached with a boost
+    pub max_percent: u32,
+    /// Per-sink overrides of ``max_percent``, keyed by the sink's ``node.name``
+    pub sink_max_percent: HashMap<String, u32>,
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self {
+            track_external_changes: true,
+            notify_external_changes: false,
+            curve: Curve::Power { exponent: 0.5 },
+            max_percent: 100,
+            sink_max_percent: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct BrightnessConfig {
+    /// Mapping between the percentage shown and the brightness given to the device
+    pub curve: Curve,
+    /// Devices which are all changed by ``set brightness monitor``, the first is the one reported
+    pub monitor: Vec<String>,
+    /// Device which is changed by ``set brightness keyboard``
+    pub keyboard: String,
+    /// Friendly names for devices, mapping to the device name
+    pub names: HashMap<String, String>,
+    /// Discover external monitors through DDC/CI
+    pub ddcutil: bool,
+}
+
+impl Default for BrightnessConfig {
+    fn default() -> Self {
+        Self {
+            curve: Curve::Linear,
+            monitor: vec![MONITOR_ID.to_string()],
+            keyboard: KEYBOARD_ID.to_string(),
+            names: HashMap::new(),
+            ddcutil: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    /// Coordinates used to calculate sunrise and sunset
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// How long the change to each slot's brightness takes, in milliseconds
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub fade: u64,
+    pub slots: Vec<ScheduleSlot>,
+}
+
+impl ScheduleConfig {
+    /// # Errors
+    /// Returns an error if a slot is relative to sunrise or sunset without coordinates
+    pub fn validate(&self) -> Result<(), DaemonError> {
+        let uses_sun = self
+            .slots
+            .iter()
+            .any(|slot| matches!(slot.time, SlotTime::Sunrise(_) | SlotTime::Sunset(_)));
+
+        if uses_sun && (self.latitude.is_none() || self.longitude.is_none()) {
+            return Err(DaemonError::ConfigError(
+                "Slots relative to sunrise or sunset need a latitude and longitude".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            latitude: None,
+            longitude: None,
+            fade: 5000,
+            slots: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct DndConfig {
+    /// Pause the notification server with ``dunstctl`` while do not disturb is on
+    pub pause_server: bool,
+    /// Pause level of the notification server, notifications with a higher ``override_pause_level`` are still shown
+    pub pause_level: u8,
+}
+
+impl Default for DndConfig {
+    fn default() -> Self {
+        Self {
+            pause_server: false,
+            pause_level: 50,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct JsonConfig {
+    /// Write every value as a string, such as ``"40"`` and ``"false"``, as the JSON output did before values had types
+    pub string_values: bool,
+    /// Sort the modules and their keys by name, instead of keeping the order of ``get``
+    pub sort_keys: bool,
+}
+
+/// Icons for each module, as names and as glyphs
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct IconsConfig {
+    /// Added to the icon names in tuples, such as ``.svg``
+    pub extension: String,
+    /// Add the file of each icon to tuples, as ``icon_path``
+    pub resolve: bool,
+    /// Icon theme which names are resolved in, falling back to ``hicolor``
+    pub theme: String,
+    /// Size and scale of the icons which are preferred when resolving
+    pub size: u32,
+    pub scale: u32,
+    /// Style of the icon given by ``get <module> icon`` when the request doesn't choose one
+    pub style: IconStyle,
+    /// Icon names, a module's map in the config file replaces its built-in map
+    #[serde(flatten)]
+    pub names: IconMaps,
+    #[serde(deserialize_with = "deserialize_glyphs")]
+    pub glyphs: IconMaps,
+}
+
+impl Default for IconsConfig {
+    fn default() -> Self {
+        Self {
+            extension: String::new(),
+            resolve: false,
+            theme: "hicolor".to_string(),
+            size: 24,
+            scale: 1,
+            style: IconStyle::Name,
+            names: IconMaps::default(),
+            glyphs: IconMaps::glyphs(),
+        }
+    }
+}
+
+impl IconsConfig {
+    #[must_use]
+    pub const fn maps(&self, style: IconStyle) -> &IconMaps {
+        match style {
+            IconStyle::Name => &self.names,
+            IconStyle::Glyph => &self.glyphs,
+        }
+    }
+}
+
+/// Values which are restored from the state file when the daemon starts
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RestoreConfig {
+    pub volume: bool,
+    pub brightness: bool,
+    pub keyboard: bool,
+    pub bluetooth: bool,
+    pub fan_profile: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(from = "NotificationsConfigFile")]
+pub struct NotificationsConfig {
+    /// How notifications are shown, through D-Bus, ``dunstify``, or not at all
+    pub backend: Notifier,
+    /// Shortest time between notifications of a module in milliseconds, the latest value is shown at the end
+    pub debounce: u64,
+    pub volume: NotificationChannel,
+    pub brightness: NotificationChannel,
+    pub bluetooth: NotificationChannel,
+    pub fan_profile: NotificationChannel,
+    pub battery: NotificationChannel,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            backend: Notifier::Dbus,
+            debounce: 100,
+            // Each module has its own replace id, so one module's notification doesn't hide another's
+            volume: NotificationChannel::with_replace_id(NOTIFICATION_ID),
+            brightness: NotificationChannel::with_replace_id(NOTIFICATION_ID + 1),
+            bluetooth: NotificationChannel {
+                actions: vec![NotificationAction::set("Turn off", "bluetooth state false")],
+                ..NotificationChannel::with_replace_id(NOTIFICATION_ID + 2)
+            },
+            fan_profile: NotificationChannel::with_replace_id(NOTIFICATION_ID + 3),
+            battery: NotificationChannel {
+                urgency: Urgency::Critical,
+                timeout: 10_000,
+                actions: vec![
+                    NotificationAction::set("Power saver", "fan-profile profile Quiet"),
+                    NotificationAction::command("Suspend", "systemctl suspend"),
+                ],
+                ..NotificationChannel::with_replace_id(NOTIFICATION_ID + 4)
+            },
+        }
+    }
+}
+
+// Channels in the config file only need the settings which differ from the module's defaults
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct NotificationsConfigFile {
+    backend: Option<Notifier>,
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    debounce: Option<u64>,
+    volume: PartialNotificationChannel,
+    brightness: PartialNotificationChannel,
+    bluetooth: PartialNotificationChannel,
+    fan_profile: PartialNotificationChannel,
+    battery: PartialNotificationChannel,
+}
+
+impl From<NotificationsConfigFile> for NotificationsConfig {
+    fn from(file: NotificationsConfigFile) -> Self {
+        let default = Self::default();
+
+        Self {
+            backend: file.backend.unwrap_or(default.backend),
+            debounce: file.debounce.unwrap_or(default.debounce),
+            volume: default.volume.merge(file.volume),
+            brightness: default.brightness.merge(file.brightness),
+            bluetooth: default.bluetooth.merge(file.bluetooth),
+            fan_profile: default.fan_profile.merge(file.fan_profile),
+            battery: default.battery.merge(file.battery),
+        }
+    }
+}
+
+// Durations are either a number of milliseconds, or a string such as ``10s``
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationValue {
+    Milliseconds(u64),
+    String(String),
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    match DurationValue::deserialize(deserializer)? {
+        DurationValue::Milliseconds(duration) => Ok(duration),
+        DurationValue::String(duration) => parse_duration(&duration).map_err(serde::de::Error::custom),
+    }
+}
+
+fn deserialize_glyphs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IconMaps, D::Error> {
+    PartialIconMaps::deserialize(deserializer).map(|partial| IconMaps::glyphs().merge(partial))
+}
+
+fn deserialize_optional_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    deserialize_duration(deserializer).map(Some)
+}
+
+impl Config {
+    /// # Errors
+    /// Returns an error if the config file exists but cannot be read
+    /// Returns an error if the config file is not valid TOML
+    /// Returns an error if the schedule is not valid
+    pub fn load() -> Result<Self, DaemonError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        // A missing config file is not an error, all values have defaults
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let config: Self = toml::from_str(&std::fs::read_to_string(path)?)?;
+        config.schedule.validate()?;
+
+        Ok(config)
+    }
+
+    /// Location of the config file, following the XDG base directory spec
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        Some(Self::dir()?.join(CONFIG_FILE_NAME))
+    }
+
+    /// Directory which holds the config file and translation catalogs
+    #[must_use]
+    pub fn dir() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(config_home.join(CONFIG_DIR_NAME))
+    }
+}
@@ -1,6 +1,12 @@
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::{UnixListener, UnixStream},
@@ -11,24 +17,46 @@ use uuid::Uuid;
 use crate::{
     battery::{Battery, BatteryItem},
     bluetooth::{Bluetooth, BluetoothItem},
-    brightness::{Brightness, BrightnessItem, KEYBOARD_ID, MONITOR_ID},
-    error::DaemonError,
+    brightness::{Brightness, BrightnessItem},
+    coalesce,
+    config::CONFIG,
+    dnd::{Dnd, DndItem},
+    error::{DaemonError, ErrorKind},
+    fade,
     fan_profile::{FanProfile, FanProfileItem},
+    json::tuples_to_json,
     listener::{handle_clients, poll_values, Client, ClientMessage, SharedClients},
+    notification::{self, Notifier},
     ram::{Ram, RamItem},
+    schedule,
     shutdown::shutdown_signal,
-    tuples::get_all_tuples,
+    state::{State, SAVE_RATE},
+    tuples::{get_all_tuples, AllTuples, Tuples},
     volume::{Volume, VolumeItem},
+    POLLING_RATE,
 };
 
 pub const SOCKET_PATH: &str = "/tmp/bar_daemon.sock";
-pub const BUFFER_SIZE: usize = 1024;
+// Largest message which is read from the socket, so a bad length can't allocate without limit
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum DaemonMessage {
-    Set { item: DaemonItem, value: String },
-    Get { item: DaemonItem },
-    Update { item: DaemonItem },
+    Set {
+        item: DaemonItem,
+        value: String,
+    },
+    Fade {
+        item: DaemonItem,
+        value: String,
+        duration: u64,
+    },
+    Get {
+        item: DaemonItem,
+    },
+    Update {
+        item: DaemonItem,
+    },
     Listen,
 }
 
@@ -40,12 +68,44 @@ pub enum DaemonReply {
     },
     Tuples {
         item: DaemonItem,
-        tuples: Vec<(String, String)>,
+        tuples: Tuples,
     },
     AllTuples {
-        tuples: Vec<(String, Vec<(String, String)>)>,
+        tuples: AllTuples,
     },
-    Error(String),
+    /// The module's values after a set request
+    Set {
+        item: DaemonItem,
+        tuples: Tuples,
+        /// Whether the request changed any value
+        changed: bool,
+        /// Whether the requested value was limited to its range, such as ``+20`` at 95% volume giving 100%
+        clamped: bool,
+    },
+    Error {
+        kind: ErrorKind,
+        message: String,
+    },
+}
+
+impl From<DaemonError> for DaemonReply {
+    fn from(e: DaemonError) -> Self {
+        Self::Error {
+            kind: e.kind(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl DaemonReply {
+    /// # Errors
+    /// Returns the error which the daemon replied with
+    pub fn into_result(self) -> Result<Self, DaemonError> {
+        match self {
+            Self::Error { kind, message } => Err(DaemonError::ReplyError { kind, message }),
+            reply => Ok(reply),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -56,9 +116,27 @@ pub enum DaemonItem {
     Battery(BatteryItem),
     Ram(RamItem),
     FanProfile(FanProfileItem),
+    Dnd(DndItem),
     All,
 }
 
+impl DaemonItem {
+    /// The message which tells the client handler that this item has been updated
+    #[must_use]
+    pub const fn get_client_message(&self) -> ClientMessage {
+        match self {
+            Self::Volume(_) => ClientMessage::UpdateVolume,
+            Self::Brightness(_) => ClientMessage::UpdateBrightness,
+            Self::Bluetooth(_) => ClientMessage::UpdateBluetooth,
+            Self::Battery(_) => ClientMessage::UpdateBattery,
+            Self::Ram(_) => ClientMessage::UpdateRam,
+            Self::FanProfile(_) => ClientMessage::UpdateFanProfile,
+            Self::Dnd(_) => ClientMessage::UpdateDnd,
+            Self::All => ClientMessage::UpdateAll,
+        }
+    }
+}
+
 /// # Errors
 /// Returns an error if ``SOCKET_PATH`` cannot be found
 /// Returns an error if ``UnixListener`` cannot be bound
@@ -82,6 +160,10 @@ pub async fn do_daemon() -> Result<(), DaemonError> {
     // Enable back and forth communication from each socket handler and the client handler
     let (clients_tx, mut clients_rx) = mpsc::unbounded_channel::<ClientMessage>();
 
+    // Restore the values which were saved before the last shutdown
+    State::restore()?;
+    clients_tx.send(ClientMessage::UpdateAll)?;
+
     // Remember listener clients to broadcast to
     let clients: SharedClients = Arc::new(Mutex::new(HashMap::new()));
 
@@ -106,6 +188,57 @@ pub async fn do_daemon() -> Result<(), DaemonError> {
         }
     });
 
+    // Create a task which saves the values that have changed
+    let notify_clone = notify.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                () = tokio::time::sleep(tokio::time::Duration::from_millis(SAVE_RATE)) => {
+                    if let Err(e) = State::flush().await {
+                        eprintln!("Could not save state:\n\t{e}");
+                    }
+                }
+                () = notify_clone.notified() => {
+                    println!("Shutdown notified, cleaning up state loop");
+                    break;
+                }
+            }
+        }
+    });
+
+    // Create tasks which follow changes made outside of the daemon
+    if CONFIG.volume.track_external_changes {
+        let clients_tx_clone = clients_tx.clone();
+        spawn_monitor("volume", notify.clone(), move || {
+            Volume::monitor(clients_tx_clone.clone())
+        });
+    }
+
+    let clients_tx_clone = clients_tx.clone();
+    spawn_monitor("keyboard backlight", notify.clone(), move || {
+        Brightness::monitor_keyboard(clients_tx_clone.clone())
+    });
+
+    // Create a task which runs the actions chosen on notifications
+    if CONFIG.notifications.backend == Notifier::Dbus {
+        let clients_tx_clone = clients_tx.clone();
+        spawn_monitor("notification action", notify.clone(), move || {
+            notification::monitor_actions(clients_tx_clone.clone())
+        });
+    }
+
+    // Create a task which turns off do not disturb when its timer runs out
+    let clients_tx_clone = clients_tx.clone();
+    spawn_monitor("do not disturb", notify.clone(), move || Dnd::monitor(clients_tx_clone.clone()));
+
+    // Create a task which follows the brightness schedule
+    if !CONFIG.schedule.slots.is_empty() {
+        let clients_tx_clone = clients_tx.clone();
+        spawn_monitor("brightness schedule", notify.clone(), move || {
+            schedule::run(clients_tx_clone.clone())
+        });
+    }
+
     // Handle sockets
     loop {
         tokio::select! {
@@ -128,6 +261,11 @@ pub async fn do_daemon() -> Result<(), DaemonError> {
         }
     }
 
+    // Save the values which changed since the last save
+    if let Err(e) = State::flush().await {
+        eprintln!("Could not save state:\n\t{e}");
+    }
+
     // Remove socket file after shutdown
     if Path::new(SOCKET_PATH).exists() {
         std::fs::remove_file(SOCKET_PATH)?;
@@ -138,10 +276,87 @@ pub async fn do_daemon() -> Result<(), DaemonError> {
     Ok(())
 }
 
+// Longest delay between restarts of a monitor which keeps failing
+const MAX_RESTART_DELAY: Duration = Duration::from_mins(5);
+// A monitor which ran for this long was working, so it is restarted quickly again
+const HEALTHY_RUN: Duration = Duration::from_mins(1);
+
+/// Delays between restarts of a monitor, which double while it keeps stopping
+///
+/// A missing command or session bus would otherwise be retried, and logged, every couple of seconds
+#[derive(Debug)]
+struct RestartBackoff {
+    delay: Duration,
+    last_error: Option<String>,
+}
+
+impl RestartBackoff {
+    const fn new() -> Self {
+        Self {
+            delay: Duration::from_millis(POLLING_RATE),
+            last_error: None,
+        }
+    }
+
+    // Returns the delay before the next restart, and the error if it wasn't logged already
+    fn stopped(&mut self, result: Result<(), DaemonError>, ran_for: Duration) -> (Duration, Option<DaemonError>) {
+        if ran_for >= HEALTHY_RUN {
+            *self = Self::new();
+        }
+
+        let error = result.err().filter(|e| {
+            let message = e.to_string();
+            let repeated = self.last_error.as_ref() == Some(&message);
+            self.last_error = Some(message);
+
+            !repeated
+        });
+
+        let delay = self.delay;
+        self.delay = (self.delay * 2).min(MAX_RESTART_DELAY);
+
+        (delay, error)
+    }
+}
+
+/// Runs a monitor until shutdown, restarting it with a growing delay while it keeps stopping
+fn spawn_monitor<F, Fut>(name: &'static str, notify: Arc<Notify>, monitor: F)
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), DaemonError>> + Send,
+{
+    tokio::spawn(async move {
+        let mut backoff = RestartBackoff::new();
+
+        loop {
+            let started = Instant::now();
+
+            let delay = tokio::select! {
+                result = monitor() => {
+                    let (delay, error) = backoff.stopped(result, started.elapsed());
+                    if let Some(e) = error {
+                        eprintln!("The {name} monitor stopped, it is restarted with a growing delay:\n\t{e}");
+                    }
+
+                    delay
+                }
+                () = notify.notified() => break,
+            };
+
+            // Restart the monitor after the delay, in case its command has exited
+            tokio::select! {
+                () = tokio::time::sleep(delay) => {}
+                () = notify.notified() => break,
+            }
+        }
+
+        println!("Shutdown notified, cleaning up {name} monitor");
+    });
+}
+
 /// # Errors
 /// Returns an error if socket cannot be read
 /// Returns an error if ``DaemonMessage`` could not be created from bytes
-/// Returns an error if requested value cannot be found or parsed
 /// Returns an error if socket could not be wrote to
 pub async fn handle_socket(
     mut stream: UnixStream,
@@ -149,81 +364,31 @@ pub async fn handle_socket(
     clients_tx: mpsc::UnboundedSender<ClientMessage>,
     notify: Arc<Notify>,
 ) -> Result<(), DaemonError> {
-    let mut buf = [0; BUFFER_SIZE];
     loop {
         tokio::select! {
-            read_result = stream.read(&mut buf) => {
-                let n = match read_result? {
-                    // Stream closed
-                    0 => break,
-                    n => n,
+            read_result = read_message(&mut stream) => {
+                // Stream closed
+                let Some(message) = read_result? else {
+                    break;
                 };
 
-                let message: DaemonMessage = postcard::from_bytes(&buf[..n])?;
-
-                let reply = match message {
-                    DaemonMessage::Set { item, value }=> {
-                        // Broadcast which value has been updated
-                        clients_tx.send(match item {
-                            DaemonItem::Volume(_) => ClientMessage::UpdateVolume,
-                            DaemonItem::Brightness(_) => ClientMessage::UpdateBrightness,
-                            DaemonItem::Bluetooth(_) => ClientMessage::UpdateBluetooth,
-                            DaemonItem::Battery(_) => ClientMessage::UpdateBattery,
-                            DaemonItem::Ram(_) => ClientMessage::UpdateRam,
-                            DaemonItem::FanProfile(_) => ClientMessage::UpdateFanProfile,
-                            DaemonItem::All => ClientMessage::UpdateAll,
-                        })?;
-
-                        match_set_command(item.clone(), value.clone())?
-                    }
-                    DaemonMessage::Get { item } => match_get_command(item.clone()).await?,
-                    DaemonMessage::Update {item} => {
-                        // Broadcast which value has been updated
-                        clients_tx.send(match item {
-                            DaemonItem::Volume(_) => {
-                                Volume::notify()?;
-
-                                ClientMessage::UpdateVolume
-                            },
-                            DaemonItem::Brightness(_) => {
-                                // TODO
-                                Brightness::notify(MONITOR_ID)?;
-                                Brightness::notify(KEYBOARD_ID)?;
-
-                                ClientMessage::UpdateBrightness
-                            },
-                            DaemonItem::Bluetooth(_) => {
-                                Bluetooth::notify()?;
-
-                                ClientMessage::UpdateBluetooth
-                            },
-                            DaemonItem::Battery(_) => {
-                                Battery::notify(u32::MAX)?;
-
-                                ClientMessage::UpdateBattery
-                            },
-                            DaemonItem::Ram(_) => ClientMessage::UpdateRam,
-                            DaemonItem::FanProfile(_) => {
-                                FanProfile::notify()?;
-
-                                ClientMessage::UpdateFanProfile
-                            },
-                            DaemonItem::All => ClientMessage::UpdateAll,
-                        })?;
-
-                        match_get_command(item.clone()).await?
-                    }
-                    DaemonMessage::Listen => {
-                        // Add the client writer and their uuid to clients
-                        let client_id = Uuid::new_v4();
-                        clients.lock().await.insert(client_id, Client { id: client_id, stream });
+                if matches!(message, DaemonMessage::Listen) {
+                    // Send the initial tuples, which may include values only the daemon knows
+                    let json = tuples_to_json(get_all_tuples().await?)? + "\n";
+                    stream.write_all(json.as_bytes()).await?;
 
-                        return Ok(());
-                    }
-                };
+                    // Add the client writer and their uuid to clients
+                    let client_id = Uuid::new_v4();
+                    clients.lock().await.insert(client_id, Client { id: client_id, stream });
+
+                    return Ok(());
+                }
+
+                // Failed requests are replied to with the error, so the connection is kept
+                let reply = reply_to_message(message, &clients_tx).await.unwrap_or_else(DaemonReply::from);
 
                 // Send the reply back
-                stream.write_all(&postcard::to_stdvec(&reply)?).await?;
+                write_message(&mut stream, &reply).await?;
             },
             () = notify.notified() => {
                 println!("Socket handler received shutdown notification");
@@ -237,6 +402,62 @@ pub async fn handle_socket(
     Ok(())
 }
 
+/// # Errors
+/// Returns an error if the requested value cannot be found or parsed
+/// Returns an error if the update cannot be sent to the client handler
+async fn reply_to_message(
+    message: DaemonMessage,
+    clients_tx: &mpsc::UnboundedSender<ClientMessage>,
+) -> Result<DaemonReply, DaemonError> {
+    Ok(match message {
+        message @ (DaemonMessage::Set { .. } | DaemonMessage::Fade { .. }) => {
+            // Sets run on a blocking thread, so requests which arrive meanwhile can be queued
+            let clients_tx = clients_tx.clone();
+            tokio::task::spawn_blocking(move || apply_set_message(message, &clients_tx)).await??
+        }
+        DaemonMessage::Get { item } => match_get_command(item.clone()).await?,
+        DaemonMessage::Update { item } => {
+            // Broadcast which value has been updated
+            clients_tx.send(match item {
+                DaemonItem::Volume(_) => {
+                    Volume::notify()?;
+
+                    ClientMessage::UpdateVolume
+                }
+                DaemonItem::Brightness(_) => {
+                    // TODO
+                    Brightness::notify_monitor()?;
+                    Brightness::notify_keyboard()?;
+
+                    ClientMessage::UpdateBrightness
+                }
+                DaemonItem::Bluetooth(_) => {
+                    Bluetooth::notify()?;
+
+                    ClientMessage::UpdateBluetooth
+                }
+                DaemonItem::Battery(_) => {
+                    Battery::notify(u32::MAX)?;
+
+                    ClientMessage::UpdateBattery
+                }
+                DaemonItem::Ram(_) => ClientMessage::UpdateRam,
+                DaemonItem::FanProfile(_) => {
+                    FanProfile::notify()?;
+
+                    ClientMessage::UpdateFanProfile
+                }
+                DaemonItem::Dnd(_) => ClientMessage::UpdateDnd,
+                DaemonItem::All => ClientMessage::UpdateAll,
+            })?;
+
+            match_get_command(item.clone()).await?
+        }
+        // Listeners are sent lines of JSON instead of a reply
+        DaemonMessage::Listen => return Err(DaemonError::ParseError("Listen has no reply".to_string())),
+    })
+}
+
 /// # Errors
 /// Returns an error if ``SOCKET_PATH`` cannot be found
 /// Returns an error if socket cannot be read
@@ -246,14 +467,130 @@ pub async fn send_daemon_messaage(message: DaemonMessage) -> Result<DaemonReply,
     let mut stream = UnixStream::connect(SOCKET_PATH).await?;
 
     // Write the serialized message to the daemon
-    stream.write_all(&postcard::to_stdvec(&message)?).await?;
+    write_message(&mut stream, &message).await?;
 
     // Get the response from the daemon
-    let mut buf = vec![0u8; BUFFER_SIZE];
-    let n = stream.read(&mut buf).await?;
+    read_message(&mut stream)
+        .await?
+        .ok_or_else(|| DaemonError::SocketError(std::io::ErrorKind::UnexpectedEof.into()))
+}
+
+/// Writes the message with postcard, prefixed by its length, so it can be read in full however large it is
+///
+/// # Errors
+/// Returns an error if the message cannot be serialized
+/// Returns an error if the message is larger than ``MAX_MESSAGE_SIZE``
+/// Returns an error if socket could not be wrote to
+pub async fn write_message<T: Serialize + Sync>(stream: &mut UnixStream, message: &T) -> Result<(), DaemonError> {
+    let bytes = postcard::to_stdvec(message)?;
+    let length = u32::try_from(bytes.len())
+        .ok()
+        .filter(|length| *length as usize <= MAX_MESSAGE_SIZE)
+        .ok_or_else(|| DaemonError::SocketError(std::io::ErrorKind::InvalidData.into()))?;
 
-    // Serialize reply into DaemonMessage
-    Ok(postcard::from_bytes(&buf[..n])?)
+    stream.write_u32(length).await?;
+    stream.write_all(&bytes).await?;
+
+    Ok(())
+}
+
+/// Reads a message written by ``write_message``, which is ``None`` when the stream closed before it
+///
+/// # Errors
+/// Returns an error if socket cannot be read
+/// Returns an error if the message is larger than ``MAX_MESSAGE_SIZE``
+/// Returns an error if the message could not be created from bytes
+pub async fn read_message<T: DeserializeOwned>(stream: &mut UnixStream) -> Result<Option<T>, DaemonError> {
+    let length = match stream.read_u32().await {
+        Ok(length) => length as usize,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    if length > MAX_MESSAGE_SIZE {
+        return Err(DaemonError::SocketError(std::io::ErrorKind::InvalidData.into()));
+    }
+
+    let mut bytes = vec![0; length];
+    stream.read_exact(&mut bytes).await?;
+
+    Ok(Some(postcard::from_bytes(&bytes)?))
+}
+
+/// Applies a set or fade request, stopping anything which would undo it
+///
+/// # Errors
+/// Returns an error if the message is not a set or fade request
+/// Returns an error if the requested value could not be parsed
+pub fn apply_set_message(
+    message: DaemonMessage,
+    clients_tx: &mpsc::UnboundedSender<ClientMessage>,
+) -> Result<DaemonReply, DaemonError> {
+    match message {
+        DaemonMessage::Set { item, value } => {
+            // Setting a value stops any fade which is changing it
+            fade::cancel(&item)?;
+
+            // Setting the monitor brightness by hand pauses the schedule
+            if matches!(item, DaemonItem::Brightness(BrightnessItem::Monitor)) {
+                schedule::set_manual_override();
+            }
+
+            apply_set_command(&item, value, clients_tx)
+        }
+        DaemonMessage::Fade { item, value, duration } => {
+            if matches!(item, DaemonItem::Brightness(BrightnessItem::Monitor)) {
+                schedule::set_manual_override();
+            }
+
+            // Updates are broadcast by the fade as it runs
+            fade::start(item, &value, duration, clients_tx.clone())
+        }
+        message => Err(DaemonError::ParseError(format!("{message:?} is not a set request"))),
+    }
+}
+
+/// Sets the item, along with any set requests for it which are queued while it is being set
+///
+/// Requests which arrive while another is running wait for the reply of their place in the queue
+///
+/// # Errors
+/// Returns an error if the requested value could not be parsed
+/// Returns an error if the set queue cannot be locked
+/// Returns an error if the request was dropped from the queue without a reply
+pub fn apply_set_command(
+    item: &DaemonItem,
+    value: String,
+    clients_tx: &mpsc::UnboundedSender<ClientMessage>,
+) -> Result<DaemonReply, DaemonError> {
+    // Requests which arrive while another is running are merged into its queue, so key-repeat doesn't pile up
+    if let Some(reply_rx) = coalesce::push(item, &value)? {
+        return Ok(reply_rx.recv()?);
+    }
+
+    let reply = set_and_broadcast(item, value, clients_tx);
+
+    // The queue is always emptied, even if a request fails, and each request is sent its own result
+    while let Some(queued) = coalesce::next(item)? {
+        let queued_reply = set_and_broadcast(item, queued.value.clone(), clients_tx).unwrap_or_else(DaemonReply::from);
+
+        queued.reply(&queued_reply);
+    }
+
+    reply
+}
+
+fn set_and_broadcast(
+    item: &DaemonItem,
+    value: String,
+    clients_tx: &mpsc::UnboundedSender<ClientMessage>,
+) -> Result<DaemonReply, DaemonError> {
+    let reply = match_set_command(item.clone(), value)?;
+
+    // Broadcast which value has been updated, once it has been set
+    clients_tx.send(item.get_client_message())?;
+
+    Ok(reply)
 }
 
 /// # Errors
@@ -264,6 +601,7 @@ pub fn match_set_command(item: DaemonItem, value: String) -> Result<DaemonReply,
         DaemonItem::Brightness(brightness_item) => Brightness::parse_item(item, &brightness_item, Some(value))?,
         DaemonItem::Bluetooth(bluetooth_item) => Bluetooth::parse_item(item, &bluetooth_item, Some(value))?,
         DaemonItem::FanProfile(fan_profile_item) => FanProfile::parse_item(item, &fan_profile_item, Some(value))?,
+        DaemonItem::Dnd(dnd_item) => Dnd::parse_item(item, &dnd_item, Some(value))?,
         _ => DaemonReply::Value { item, value },
     };
 
@@ -280,6 +618,7 @@ pub async fn match_get_command(item: DaemonItem) -> Result<DaemonReply, DaemonEr
         DaemonItem::Battery(battery_item) => Battery::parse_item(item.clone(), &battery_item)?,
         DaemonItem::Ram(ram_item) => Ram::parse_item(item.clone(), &ram_item)?,
         DaemonItem::FanProfile(fan_profile_item) => FanProfile::parse_item(item.clone(), &fan_profile_item, None)?,
+        DaemonItem::Dnd(dnd_item) => Dnd::parse_item(item.clone(), &dnd_item, None)?,
         DaemonItem::All => DaemonReply::AllTuples {
             tuples: get_all_tuples().await?,
         },
@@ -287,3 +626,108 @@ pub async fn match_get_command(item: DaemonItem) -> Result<DaemonReply, DaemonEr
 
     Ok(message)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tuples::TupleValue;
+
+    // A reply like ``get`` with ``icons.resolve``, which is larger than a single read used to be
+    fn large_reply() -> DaemonReply {
+        DaemonReply::AllTuples {
+            tuples: (0..16)
+                .map(|module| {
+                    (
+                        format!("module_{module}"),
+                        vec![
+                            ("percent".to_string(), TupleValue::Int(module)),
+                            (
+                                "icon_path".to_string(),
+                                format!("/usr/share/icons/Adwaita/symbolic/status/icon-{module}-symbolic.svg").into(),
+                            ),
+                            ("glyph".to_string(), "󰕾".into()),
+                        ],
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_replies_larger_than_a_read() -> Result<(), DaemonError> {
+        let (mut daemon, mut client) = UnixStream::pair()?;
+        let reply = large_reply();
+
+        assert!(postcard::to_stdvec(&reply)?.len() > 1024);
+
+        write_message(&mut daemon, &reply).await?;
+        drop(daemon);
+
+        let read_reply = read_message::<DaemonReply>(&mut client).await?;
+        assert_eq!(format!("{read_reply:?}"), format!("{:?}", Some(reply)));
+
+        // The stream closing between messages isn't an error
+        assert!(read_message::<DaemonReply>(&mut client).await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_messages_over_the_limit() -> Result<(), DaemonError> {
+        let (mut daemon, mut client) = UnixStream::pair()?;
+
+        daemon.write_u32(u32::MAX).await?;
+
+        assert!(read_message::<DaemonReply>(&mut client).await.is_err());
+
+        Ok(())
+    }
+
+    fn missing_command() -> Result<(), DaemonError> {
+        Err(DaemonError::StreamNotFound("pactl".to_string()))
+    }
+
+    #[test]
+    fn repeated_failures_are_logged_once_and_back_off() {
+        let mut backoff = RestartBackoff::new();
+        let quick = Duration::from_millis(10);
+
+        let (delay, error) = backoff.stopped(missing_command(), quick);
+        assert_eq!(delay, Duration::from_millis(POLLING_RATE));
+        assert!(error.is_some());
+
+        let (delay, error) = backoff.stopped(missing_command(), quick);
+        assert_eq!(delay, Duration::from_millis(POLLING_RATE * 2));
+        assert!(error.is_none());
+
+        let delays = (0..16)
+            .map(|_| backoff.stopped(missing_command(), quick).0)
+            .collect::<Vec<_>>();
+        assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(delays.last(), Some(&MAX_RESTART_DELAY));
+
+        // A different error is logged again, without resetting the delay
+        let (delay, error) = backoff.stopped(Err(DaemonError::MutexLockError), quick);
+        assert_eq!(delay, MAX_RESTART_DELAY);
+        assert!(error.is_some());
+    }
+
+    #[test]
+    fn healthy_runs_reset_the_backoff() {
+        let mut backoff = RestartBackoff::new();
+
+        for _ in 0..4 {
+            let _ = backoff.stopped(missing_command(), Duration::ZERO);
+        }
+
+        // The monitor worked for a while, so its next failure is logged and retried quickly
+        let (delay, error) = backoff.stopped(missing_command(), HEALTHY_RUN);
+        assert_eq!(delay, Duration::from_millis(POLLING_RATE));
+        assert!(error.is_some());
+
+        // Monitors which exit cleanly straight away are still delayed more each time
+        let (delay, error) = backoff.stopped(Ok(()), Duration::ZERO);
+        assert_eq!(delay, Duration::from_millis(POLLING_RATE * 2));
+        assert!(error.is_none());
+    }
+}
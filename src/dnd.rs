@@ -0,0 +1,275 @@
+use std::sync::{LazyLock, Mutex};
+
+use clap::Subcommand;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    cli::parse_duration,
+    command,
+    config::CONFIG,
+    daemon::{DaemonItem, DaemonMessage, DaemonReply},
+    error::DaemonError,
+    icon::{self, on_off, IconStyle},
+    listener::ClientMessage,
+    tuples::Tuples,
+    POLLING_RATE,
+};
+
+// Whether do not disturb is on, and when it turns itself off
+static DND: LazyLock<Mutex<(bool, Option<Instant>)>> = LazyLock::new(|| Mutex::new((false, None)));
+
+#[derive(Subcommand)]
+pub enum DndGetCommands {
+    #[command(alias = "s")]
+    State,
+    #[command(alias = "rem", alias = "r")]
+    Remaining,
+    #[command(alias = "i")]
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DndSetCommands {
+    On,
+    Off,
+    #[command(alias = "t")]
+    Toggle,
+    /// Turns do not disturb on, then off again after the duration
+    For {
+        #[arg(value_parser = parse_duration)]
+        duration: u64,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DndUpdateCommands {
+    #[command(alias = "s")]
+    State,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum DndItem {
+    State,
+    Remaining,
+    Icon(Option<IconStyle>),
+    All,
+}
+
+pub struct Dnd;
+
+impl Dnd {
+    /// # Errors
+    /// Returns an error if the do not disturb mutex cannot be locked
+    pub fn get_state() -> Result<bool, DaemonError> {
+        let (state, until) = *DND.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+        // The timer may have run out before the monitor has turned it off
+        Ok(state && until.is_none_or(|until| until > Instant::now()))
+    }
+
+    /// Seconds until do not disturb turns itself off, 0 if it has no timer
+    ///
+    /// # Errors
+    /// Returns an error if the do not disturb mutex cannot be locked
+    pub fn get_remaining() -> Result<u64, DaemonError> {
+        let until = DND.lock().map_err(|_| DaemonError::MutexLockError)?.1;
+
+        Ok(until.map_or(0, |until| {
+            until.saturating_duration_since(Instant::now()).as_secs_f64().ceil() as u64
+        }))
+    }
+
+    /// Notifications which aren't critical are hidden while do not disturb is on
+    #[must_use]
+    pub fn is_enabled() -> bool {
+        Self::get_state().unwrap_or(false)
+    }
+
+    /// Accepts ``on``, ``off``, ``toggle``, a bool, or a duration to turn it on for
+    ///
+    /// # Errors
+    /// Returns an error if the value is not a state or a duration
+    /// Returns an error if the do not disturb mutex cannot be locked
+    /// Returns an error if the notification server cannot be paused
+    pub fn set_state(value: &str) -> Result<(), DaemonError> {
+        let (state, until) = match value.trim() {
+            "on" | "true" | "1" => (true, None),
+            "off" | "false" | "0" => (false, None),
+            "toggle" => (!Self::get_state()?, None),
+            duration => (
+                true,
+                Some(
+                    Instant::now()
+                        + tokio::time::Duration::from_millis(
+                            parse_duration(duration).map_err(DaemonError::ParseError)?,
+                        ),
+                ),
+            ),
+        };
+
+        let prev_state = std::mem::replace(
+            &mut *DND.lock().map_err(|_| DaemonError::MutexLockError)?,
+            (state, until),
+        )
+        .0;
+
+        // Keep the notification server in step, so other applications are paused too
+        if CONFIG.dnd.pause_server && prev_state != state {
+            let args = pause_server_args(state, CONFIG.dnd.pause_level);
+            command::run("dunstctl", &[args[0].as_str(), args[1].as_str()])?;
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get_icon(style: IconStyle, state: bool) -> String {
+        CONFIG.icons.maps(style).dnd.get(Some(on_off(state)), 0)
+    }
+
+    /// # Errors
+    /// Returns an error if the do not disturb mutex cannot be locked
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
+        let state = Self::get_state()?;
+        let icon = Self::get_icon(IconStyle::Name, state);
+        let glyph = Self::get_icon(IconStyle::Glyph, state);
+
+        Ok([
+            vec![
+                ("state".to_string(), state.into()),
+                ("remaining".to_string(), Self::get_remaining()?.into()),
+            ],
+            icon::to_tuples("", &icon, &glyph),
+        ]
+        .concat())
+    }
+
+    /// Turns do not disturb off when its timer runs out, updating the remaining time while it counts down
+    ///
+    /// # Errors
+    /// Returns an error if the do not disturb mutex cannot be locked
+    /// Returns an error if the update cannot be sent to the client handler
+    pub async fn monitor(clients_tx: mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_millis(POLLING_RATE)).await;
+
+            let until = DND.lock().map_err(|_| DaemonError::MutexLockError)?.1;
+
+            let Some(until) = until else {
+                continue;
+            };
+
+            if until <= Instant::now() {
+                Self::set_state("off")?;
+            }
+
+            clients_tx.send(ClientMessage::UpdateDnd)?;
+        }
+    }
+
+    /// # Errors
+    /// Returns an error if the requested value could not be parsed
+    pub fn parse_item(item: DaemonItem, dnd_item: &DndItem, value: Option<String>) -> Result<DaemonReply, DaemonError> {
+        Ok(if let Some(value) = value {
+            let prev_state = (Self::get_state()?, Self::get_remaining()?);
+
+            // Set value
+            if dnd_item == &DndItem::State {
+                Self::set_state(value.as_str())?;
+            }
+
+            let changed = prev_state != (Self::get_state()?, Self::get_remaining()?);
+
+            DaemonReply::Set {
+                item,
+                tuples: Self::get_tuples()?,
+                changed,
+                clamped: false,
+            }
+        } else {
+            // Get value
+            match dnd_item {
+                DndItem::State => DaemonReply::Value {
+                    item,
+                    value: Self::get_state()?.to_string(),
+                },
+                DndItem::Remaining => DaemonReply::Value {
+                    item,
+                    value: Self::get_remaining()?.to_string(),
+                },
+                DndItem::Icon(style) => DaemonReply::Value {
+                    item,
+                    value: Self::get_icon(style.unwrap_or(CONFIG.icons.style), Self::get_state()?),
+                },
+                DndItem::All => DaemonReply::Tuples {
+                    item,
+                    tuples: Self::get_tuples()?,
+                },
+            }
+        })
+    }
+
+    #[must_use]
+    pub const fn match_get_commands(commands: &Option<DndGetCommands>) -> DaemonMessage {
+        DaemonMessage::Get {
+            item: match commands {
+                Some(commands) => match commands {
+                    DndGetCommands::State => DaemonItem::Dnd(DndItem::State),
+                    DndGetCommands::Remaining => DaemonItem::Dnd(DndItem::Remaining),
+                    DndGetCommands::Icon { style } => DaemonItem::Dnd(DndItem::Icon(*style)),
+                },
+                None => DaemonItem::Dnd(DndItem::All),
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn match_set_commands(commands: &DndSetCommands) -> DaemonMessage {
+        DaemonMessage::Set {
+            item: DaemonItem::Dnd(DndItem::State),
+            value: match commands {
+                DndSetCommands::On => "on".to_string(),
+                DndSetCommands::Off => "off".to_string(),
+                DndSetCommands::Toggle => "toggle".to_string(),
+                DndSetCommands::For { duration } => duration.to_string(),
+            },
+        }
+    }
+
+    #[must_use]
+    pub const fn match_update_commands(commands: &DndUpdateCommands) -> DaemonMessage {
+        match commands {
+            DndUpdateCommands::State => DaemonMessage::Update {
+                item: DaemonItem::Dnd(DndItem::State),
+            },
+        }
+    }
+}
+
+// A pause level rather than a full pause, so critical notifications which dunst gives a higher
+// ``override_pause_level`` are still shown without resuming the server
+fn pause_server_args(state: bool, pause_level: u8) -> [String; 2] {
+    let pause_level = if state { pause_level } else { 0 };
+
+    ["set-pause-level".to_string(), pause_level.to_string()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_server_to_level() {
+        assert_eq!(pause_server_args(true, 50), ["set-pause-level", "50"]);
+        assert_eq!(pause_server_args(true, 100), ["set-pause-level", "100"]);
+    }
+
+    #[test]
+    fn resumes_server_fully() {
+        assert_eq!(pause_server_args(false, 50), ["set-pause-level", "0"]);
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::mpsc;
 
@@ -32,15 +35,102 @@ pub enum DaemonError {
     #[error("Serde JSON Serialization Failed:\n\t{0}")]
     JsonError(#[from] serde_json::Error),
 
+    #[error("Config File Could Not Be Parsed:\n\t{0}")]
+    TomlError(#[from] toml::de::Error),
+
+    #[error("D-Bus Call Failed:\n\t{0}")]
+    DbusError(#[from] zbus::Error),
+
     #[error("Mpsc Could Not Send ClientMessage:\n\t{0}")]
     MpscSendError(#[from] mpsc::error::SendError<ClientMessage>),
 
     #[error("Could not convert between int types:\n\t{0}")]
     IntError(#[from] std::num::TryFromIntError),
 
+    #[error("Task could not be joined:\n\t{0}")]
+    JoinError(#[from] tokio::task::JoinError),
+
+    #[error("Queued set request was not replied to:\n\t{0}")]
+    QueueReplyError(#[from] std::sync::mpsc::RecvError),
+
     #[error("Mutex couldn't be locked")]
     MutexLockError,
 
+    #[error("No playback stream found for application '{0}'")]
+    StreamNotFound(String),
+
+    #[error("No brightness device found with the name '{0}'")]
+    DeviceNotFound(String),
+
+    #[error("Brightness schedule could not be followed:\n\t{0}")]
+    ScheduleError(String),
+
+    #[error("Config is not valid:\n\t{0}")]
+    ConfigError(String),
+
+    #[error("Item {0} was not handled")]
+    UnhandledItem(String),
+
     #[error("Could not convert usize to TupleName")]
     TupleNameError,
+
+    #[error("The daemon replied with a {kind} error:\n\t{message}")]
+    ReplyError { kind: ErrorKind, message: String },
+}
+
+/// What went wrong, for clients which handle some errors differently
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// An external command failed, such as ``wpctl`` or ``brightnessctl``
+    Command,
+    /// A value could not be parsed, such as a percent of ``+abc``
+    Parse,
+    /// A brightness device or playback stream does not exist
+    NotFound,
+    Dbus,
+    Io,
+    Internal,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Command => "command",
+            Self::Parse => "parse",
+            Self::NotFound => "not_found",
+            Self::Dbus => "dbus",
+            Self::Io => "io",
+            Self::Internal => "internal",
+        })
+    }
+}
+
+impl DaemonError {
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        match self {
+            Self::CommandError { .. } => ErrorKind::Command,
+            Self::IntegerFromByteString(_)
+            | Self::IntegerFromString(_)
+            | Self::BoolFromString(_)
+            | Self::StringToFloatError(_)
+            | Self::ParseError(_)
+            | Self::ConfigError(_)
+            | Self::IntError(_)
+            | Self::TupleNameError => ErrorKind::Parse,
+            Self::StreamNotFound(_) | Self::DeviceNotFound(_) => ErrorKind::NotFound,
+            Self::DbusError(_) => ErrorKind::Dbus,
+            Self::SocketError(_) => ErrorKind::Io,
+            Self::PostcardError(_)
+            | Self::JsonError(_)
+            | Self::TomlError(_)
+            | Self::MpscSendError(_)
+            | Self::JoinError(_)
+            | Self::QueueReplyError(_)
+            | Self::MutexLockError
+            | Self::UnhandledItem(_)
+            | Self::ScheduleError(_) => ErrorKind::Internal,
+            Self::ReplyError { kind, .. } => *kind,
+        }
+    }
 }
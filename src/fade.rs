@@ -0,0 +1,211 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        LazyLock, Mutex,
+    },
+};
+
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    brightness::{Brightness, BrightnessItem},
+    daemon::{match_set_command, DaemonItem, DaemonReply},
+    error::DaemonError,
+    listener::ClientMessage,
+    volume::{Volume, VolumeItem},
+};
+
+// Shortest time between each step of a fade
+const FADE_STEP_RATE: u64 = 25;
+// Listener clients are updated at most this often during a fade
+const FADE_UPDATE_RATE: u64 = 100;
+
+// In-flight fades, keyed by the value which they are changing
+static FADES: LazyLock<Mutex<HashMap<String, Fade>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+struct Fade {
+    // Tells fades apart, so a finished fade doesn't remove the one which replaced it
+    id: u64,
+    handle: JoinHandle<()>,
+}
+
+fn get_key(item: &DaemonItem) -> Option<String> {
+    match item {
+        DaemonItem::Volume(VolumeItem::Percent | VolumeItem::Boost) => Some("volume".to_string()),
+        DaemonItem::Brightness(BrightnessItem::Monitor) => Some("brightness_monitor".to_string()),
+        DaemonItem::Brightness(BrightnessItem::Keyboard) => Some("brightness_keyboard".to_string()),
+        DaemonItem::Brightness(BrightnessItem::Device(name)) => Some(format!("brightness_device_{name}")),
+        _ => None,
+    }
+}
+
+/// Stops the fade which is changing this item, if there is one
+///
+/// # Errors
+/// Returns an error if the fades mutex cannot be locked
+pub fn cancel(item: &DaemonItem) -> Result<(), DaemonError> {
+    let Some(key) = get_key(item) else {
+        return Ok(());
+    };
+
+    let fade = FADES.lock().map_err(|_| DaemonError::MutexLockError)?.remove(&key);
+
+    if let Some(fade) = fade {
+        fade.handle.abort();
+    }
+
+    Ok(())
+}
+
+/// Gradually changes the item to ``value`` over ``duration`` milliseconds, in a background task
+///
+/// The reply has the percentage which the fade ends at, as the fade has only just started
+///
+/// # Errors
+/// Returns an error if the current or target percentage cannot be found
+/// Returns an error if the fades mutex cannot be locked
+pub fn start(
+    item: DaemonItem,
+    value: &str,
+    duration: u64,
+    clients_tx: mpsc::UnboundedSender<ClientMessage>,
+) -> Result<DaemonReply, DaemonError> {
+    let Some(key) = get_key(&item) else {
+        // Items which can't be faded are set straight away
+        let client_message = item.get_client_message();
+        let reply = match_set_command(item, value.to_string())?;
+        clients_tx.send(client_message)?;
+
+        return Ok(reply);
+    };
+
+    // A new fade replaces any which is already running
+    cancel(&item)?;
+
+    let start_percent = get_percent(&item)?;
+    let (target_percent, clamped) = get_target_percent(&item, value)?;
+
+    let reply = DaemonReply::Set {
+        item: item.clone(),
+        tuples: vec![("target_percent".to_string(), target_percent.into())],
+        changed: start_percent != target_percent,
+        clamped,
+    };
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+    // Held until the fade is inserted, so a fade which finishes straight away can't remove itself first
+    let mut fades = FADES.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+    let fade_key = key.clone();
+    let handle = tokio::spawn(async move {
+        if let Err(e) = run(&item, start_percent, target_percent, duration, &clients_tx).await {
+            eprintln!("Fade could not finish:\n\t{e}");
+        }
+
+        if let Err(e) = remove(&fade_key, id) {
+            eprintln!("Fade could not be removed:\n\t{e}");
+        }
+    });
+
+    fades.insert(key, Fade { id, handle });
+    drop(fades);
+
+    Ok(reply)
+}
+
+// Forgets a fade which has finished, unless another fade has replaced it
+fn remove(key: &str, id: u64) -> Result<(), DaemonError> {
+    let mut fades = FADES.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+    if fades.get(key).is_some_and(|fade| fade.id == id) {
+        fades.remove(key);
+    }
+
+    drop(fades);
+
+    Ok(())
+}
+
+async fn run(
+    item: &DaemonItem,
+    start_percent: u32,
+    target_percent: u32,
+    duration: u64,
+    clients_tx: &mpsc::UnboundedSender<ClientMessage>,
+) -> Result<(), DaemonError> {
+    let difference = i64::from(target_percent) - i64::from(start_percent);
+
+    // Never step faster than the step rate, or by less than a whole percent
+    let steps = (duration / FADE_STEP_RATE).min(difference.unsigned_abs()).max(1);
+    let step_duration = Duration::from_millis(duration / steps);
+
+    let mut last_update = Instant::now();
+
+    for step in 1..=steps {
+        tokio::time::sleep(step_duration).await;
+
+        let percent =
+            u32::try_from(i64::from(start_percent) + difference * i64::try_from(step)? / i64::try_from(steps)?)?;
+
+        // The commands block, so they are kept off the async workers
+        let step_item = item.clone();
+        tokio::task::spawn_blocking(move || set_quiet(&step_item, percent)).await??;
+
+        // Throttle the updates which are sent to listeners
+        if last_update.elapsed() >= Duration::from_millis(FADE_UPDATE_RATE) {
+            clients_tx.send(item.get_client_message())?;
+
+            last_update = Instant::now();
+        }
+    }
+
+    clients_tx.send(item.get_client_message())?;
+
+    if start_percent != target_percent {
+        let item = item.clone();
+        tokio::task::spawn_blocking(move || notify(&item)).await??;
+    }
+
+    Ok(())
+}
+
+fn get_percent(item: &DaemonItem) -> Result<u32, DaemonError> {
+    match item {
+        DaemonItem::Brightness(brightness_item) => Brightness::get_item(brightness_item),
+        _ => Volume::get_percent(),
+    }
+}
+
+fn get_target_percent(item: &DaemonItem, value: &str) -> Result<(u32, bool), DaemonError> {
+    match item {
+        DaemonItem::Volume(volume_item) => Volume::get_target_percent(volume_item, value),
+        DaemonItem::Brightness(brightness_item) => Brightness::get_target_percent(brightness_item, value),
+        _ => Err(DaemonError::ParseError(value.to_string())),
+    }
+}
+
+fn set_quiet(item: &DaemonItem, percent: u32) -> Result<(), DaemonError> {
+    match item {
+        DaemonItem::Volume(VolumeItem::Boost) => Volume::set_boost(percent.to_string().as_str()),
+        DaemonItem::Volume(_) => Volume::set_percent(percent.to_string().as_str()),
+        DaemonItem::Brightness(brightness_item) => {
+            Brightness::set_item_quiet(brightness_item, percent.to_string().as_str())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn notify(item: &DaemonItem) -> Result<(), DaemonError> {
+    match item {
+        DaemonItem::Volume(_) => Volume::notify(),
+        DaemonItem::Brightness(brightness_item) => Brightness::notify_item(brightness_item),
+        _ => Ok(()),
+    }
+}
@@ -3,9 +3,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     command,
+    config::CONFIG,
     daemon::{DaemonItem, DaemonMessage, DaemonReply},
     error::DaemonError,
-    ICON_END, ICON_EXT, NOTIFICATION_ID, NOTIFICATION_TIMEOUT,
+    icon::{self, IconStyle},
+    locale::translate,
+    notification::Notification,
+    tuples::Tuples,
 };
 
 #[derive(Eq, PartialEq, Debug, Clone, Copy)]
@@ -15,14 +19,17 @@ pub enum FanState {
     Quiet = 2,
 }
 
-const FAN_STATE_STRINGS: &[&str] = &["Performance", "Balanced", "Quiet"];
+pub const FAN_STATE_STRINGS: &[&str] = &["Performance", "Balanced", "Quiet"];
 
 #[derive(Subcommand)]
 pub enum FanProfileGetCommands {
     #[command(alias = "prof", alias = "p")]
     Profile,
     #[command(alias = "i")]
-    Icon,
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -43,7 +50,7 @@ pub enum FanProfileUpdateCommands {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum FanProfileItem {
     Profile,
-    Icon,
+    Icon(Option<IconStyle>),
 }
 
 pub struct FanProfile;
@@ -104,21 +111,30 @@ impl FanProfile {
     }
 
     #[must_use]
-    pub fn get_icon() -> String {
-        format!("sensors-fan{ICON_END}")
+    pub fn get_icon(style: IconStyle, profile: FanState) -> String {
+        let state = FAN_STATE_STRINGS[profile as usize];
+
+        CONFIG.icons.maps(style).fan_profile.get(Some(state), 0)
     }
 
     /// # Errors
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
-    pub fn get_tuples() -> Result<Vec<(String, String)>, DaemonError> {
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
         let profile = Self::get_profile()?;
-        let icon = Self::get_icon();
-
-        Ok(vec![
-            ("profile".to_string(), FAN_STATE_STRINGS[profile as usize].to_string()),
-            ("icon".to_string(), format!("{icon}{ICON_EXT}")),
-        ])
+        let icon = Self::get_icon(IconStyle::Name, profile);
+        let glyph = Self::get_icon(IconStyle::Glyph, profile);
+        let profile_string = FAN_STATE_STRINGS[profile as usize];
+
+        Ok([
+            vec![
+                // The profile can be given back to `set`, so only the label is translated
+                ("profile".to_string(), profile_string.into()),
+                ("profile_label".to_string(), translate(profile_string).into()),
+            ],
+            icon::to_tuples("", &icon, &glyph),
+        ]
+        .concat())
     }
 
     /// # Errors
@@ -137,13 +153,19 @@ impl FanProfile {
             }
 
             let new_profile = Self::get_profile()?;
+            let changed = prev_profile != new_profile;
 
-            if prev_profile != new_profile {
+            if changed {
                 // Do a notification
                 Self::notify()?;
             }
 
-            DaemonReply::Value { item, value }
+            DaemonReply::Set {
+                item,
+                tuples: Self::get_tuples()?,
+                changed,
+                clamped: false,
+            }
         } else {
             // Get value
             match fan_profile_item {
@@ -151,9 +173,9 @@ impl FanProfile {
                     item,
                     value: FAN_STATE_STRINGS[Self::get_profile()? as usize].to_string(),
                 },
-                FanProfileItem::Icon => DaemonReply::Value {
+                FanProfileItem::Icon(style) => DaemonReply::Value {
                     item,
-                    value: Self::get_icon(),
+                    value: Self::get_icon(style.unwrap_or(CONFIG.icons.style), Self::get_profile()?),
                 },
             }
         })
@@ -164,7 +186,7 @@ impl FanProfile {
         DaemonMessage::Get {
             item: match commands {
                 FanProfileGetCommands::Profile => DaemonItem::FanProfile(FanProfileItem::Profile),
-                FanProfileGetCommands::Icon => DaemonItem::FanProfile(FanProfileItem::Icon),
+                FanProfileGetCommands::Icon { style } => DaemonItem::FanProfile(FanProfileItem::Icon(*style)),
             },
         }
     }
@@ -192,23 +214,14 @@ impl FanProfile {
     /// Returns an error if the requested value could not be parsed
     pub fn notify() -> Result<(), DaemonError> {
         let profile = Self::get_profile()?;
-        let icon = Self::get_icon();
-
-        command::run(
-            "dunstify",
-            &[
-                "-u",
-                "-normal",
-                "-t",
-                NOTIFICATION_TIMEOUT.to_string().as_str(),
-                "-i",
-                icon.as_str(),
-                "-r",
-                NOTIFICATION_ID.to_string().as_str(),
-                format!("Fan Profile: {}", FAN_STATE_STRINGS[profile as usize]).as_str(),
-            ],
-        )?;
+        let icon = Self::get_icon(IconStyle::Name, profile);
 
-        Ok(())
+        Notification::new(
+            &CONFIG.notifications.fan_profile,
+            "Fan Profile: {state}",
+            &icon,
+            &[("state", FAN_STATE_STRINGS[profile as usize])],
+        )
+        .send()
     }
 }
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::{
+    cli::parse_set_message,
+    config::CONFIG,
+    daemon::send_daemon_messaage,
+    error::DaemonError,
+    notification::render,
+    output::{get_percentage, placeholders},
+    tuples::{TupleValue, Tuples, TUPLE_NAMES},
+};
+
+/// First line of the i3bar protocol, the blocks follow as an infinite array
+pub const HEADER: &str = r#"{"version":1,"click_events":true}"#;
+
+/// A colour which the block has while the module's percentage is between ``min`` and ``max`` (Inclusive)
+#[derive(Deserialize, Debug, Clone)]
+pub struct I3barColor {
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub color: String,
+}
+
+impl I3barColor {
+    fn new(min: Option<u32>, max: Option<u32>, color: &str) -> Self {
+        Self {
+            min,
+            max,
+            color: color.to_string(),
+        }
+    }
+
+    fn matches(&self, percentage: u32) -> bool {
+        self.min.is_none_or(|min| percentage >= min) && self.max.is_none_or(|max| percentage <= max)
+    }
+}
+
+/// How a module is shown as an i3bar block, the text takes the module's ``{key}`` placeholders
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct I3barModule {
+    pub enabled: bool,
+    pub text: String,
+    /// Key of the tuple which is used for the colours
+    pub percentage: Option<String>,
+    pub colors: Vec<I3barColor>,
+    /// Arguments of the set command which is sent for each button
+    ///
+    /// The buttons are ``left``, ``middle``, ``right``, ``scroll_up`` and ``scroll_down``
+    pub clicks: HashMap<String, String>,
+}
+
+impl Default for I3barModule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            text: "{glyph}".to_string(),
+            percentage: None,
+            colors: Vec::new(),
+            clicks: HashMap::new(),
+        }
+    }
+}
+
+impl I3barModule {
+    fn new(text: &str, percentage: Option<&str>, clicks: &[(&str, &str)]) -> Self {
+        Self {
+            text: text.to_string(),
+            percentage: percentage.map(str::to_string),
+            clicks: clicks
+                .iter()
+                .map(|(button, args)| ((*button).to_string(), (*args).to_string()))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    fn to_block(&self, name: &str, tuples: &[(String, TupleValue)]) -> Value {
+        let placeholders = placeholders(tuples);
+
+        let mut block = json!({
+            "name": name,
+            "full_text": render(&self.text, &placeholders),
+        });
+
+        let color = self
+            .percentage
+            .as_ref()
+            .and_then(|key| get_percentage(tuples, key))
+            .and_then(|percentage| self.colors.iter().find(|color| color.matches(percentage)));
+
+        if let (Some(color), Some(object)) = (color, block.as_object_mut()) {
+            object.insert("color".to_string(), json!(color.color));
+        }
+
+        block
+    }
+}
+
+/// i3bar settings of each module, keyed by the module's name in ``get``
+#[derive(Debug)]
+pub struct I3barConfig(pub HashMap<String, I3barModule>);
+
+impl Default for I3barConfig {
+    fn default() -> Self {
+        Self(HashMap::from([
+            (
+                "volume".to_string(),
+                I3barModule::new(
+                    "{glyph} {percent}%",
+                    Some("percent"),
+                    &[
+                        ("left", "volume mute"),
+                        ("scroll_up", "volume percent +5"),
+                        ("scroll_down", "volume percent -5"),
+                    ],
+                ),
+            ),
+            (
+                "brightness".to_string(),
+                I3barModule::new(
+                    "{glyph} {monitor_percent}%",
+                    Some("monitor_percent"),
+                    &[
+                        ("scroll_up", "brightness monitor +5"),
+                        ("scroll_down", "brightness monitor -5"),
+                    ],
+                ),
+            ),
+            (
+                "bluetooth".to_string(),
+                I3barModule::new("{glyph}", None, &[("left", "bluetooth state")]),
+            ),
+            (
+                "battery".to_string(),
+                I3barModule {
+                    colors: vec![
+                        I3barColor::new(None, Some(15), "#ff5555"),
+                        I3barColor::new(Some(16), Some(30), "#ffb86c"),
+                    ],
+                    ..I3barModule::new("{glyph} {percent}%", Some("percent"), &[])
+                },
+            ),
+            (
+                "ram".to_string(),
+                I3barModule {
+                    colors: vec![I3barColor::new(Some(90), None, "#ff5555")],
+                    ..I3barModule::new("{glyph} {percent}%", Some("percent"), &[])
+                },
+            ),
+            (
+                "fan_profile".to_string(),
+                I3barModule::new(
+                    "{glyph} {profile_label}",
+                    None,
+                    &[
+                        ("left", "fan-profile profile next"),
+                        ("right", "fan-profile profile prev"),
+                    ],
+                ),
+            ),
+            (
+                "dnd".to_string(),
+                I3barModule::new("{glyph}", None, &[("left", "dnd toggle")]),
+            ),
+        ]))
+    }
+}
+
+impl<'de> Deserialize<'de> for I3barConfig {
+    // Modules in the config file replace their defaults, the others are kept
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let modules = HashMap::<String, I3barModule>::deserialize(deserializer)?;
+
+        let mut config = Self::default();
+        config.0.extend(modules);
+
+        Ok(config)
+    }
+}
+
+/// The blocks of every enabled module, in the order of ``TUPLE_NAMES``, as an element of the infinite array
+#[must_use]
+pub fn to_blocks(tuples: &[(String, Tuples)]) -> String {
+    let blocks = TUPLE_NAMES
+        .iter()
+        .filter_map(|&name| {
+            let module = CONFIG.i3bar.0.get(name).filter(|module| module.enabled)?;
+            let (_, tuples) = tuples.iter().find(|(tuple_name, _)| tuple_name == name)?;
+
+            Some(module.to_block(name, tuples))
+        })
+        .collect::<Vec<_>>();
+
+    format!("{},", Value::Array(blocks))
+}
+
+#[derive(Deserialize)]
+struct ClickEvent {
+    name: String,
+    button: u32,
+}
+
+/// Reads click events from stdin, sending the set command of the module's button to the daemon
+///
+/// # Errors
+/// Returns an error if stdin cannot be read
+pub async fn read_clicks() -> Result<(), DaemonError> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        // The events are an infinite array, so each event after the first starts with a comma
+        let line = line.trim().trim_start_matches(['[', ',']).trim();
+
+        let Ok(event) = serde_json::from_str::<ClickEvent>(line) else {
+            continue;
+        };
+
+        // stdout belongs to the bar, so errors are only logged
+        if let Err(e) = click(&event).await {
+            eprintln!("Could not handle click on {}:\n\t{e}", event.name);
+        }
+    }
+
+    Ok(())
+}
+
+async fn click(event: &ClickEvent) -> Result<(), DaemonError> {
+    let button = match event.button {
+        1 => "left",
+        2 => "middle",
+        3 => "right",
+        4 => "scroll_up",
+        5 => "scroll_down",
+        _ => return Ok(()),
+    };
+
+    let Some(args) = CONFIG
+        .i3bar
+        .0
+        .get(&event.name)
+        .and_then(|module| module.clicks.get(button))
+    else {
+        return Ok(());
+    };
+
+    send_daemon_messaage(parse_set_message(args)?).await?.into_result()?;
+
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::CONFIG, icon_theme, tuples::Tuples, ICON_END};
+
+/// Whether icons are freedesktop icon names, or glyphs for text-only bars
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+    #[default]
+    Name,
+    /// Unicode and Nerd Font characters
+    Glyph,
+}
+
+/// Icon maps of every module
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct IconMaps {
+    pub volume: IconMap,
+    pub monitor: IconMap,
+    pub keyboard: IconMap,
+    pub battery: IconMap,
+    pub bluetooth: IconMap,
+    pub dnd: IconMap,
+    pub fan_profile: IconMap,
+    pub ram: IconMap,
+}
+
+impl Default for IconMaps {
+    fn default() -> Self {
+        Self {
+            volume: default_volume(),
+            monitor: default_monitor(),
+            keyboard: default_keyboard(),
+            battery: default_battery(),
+            bluetooth: default_bluetooth(),
+            dnd: default_dnd(),
+            fan_profile: default_fan_profile(),
+            ram: default_ram(),
+        }
+    }
+}
+
+impl IconMaps {
+    /// Nerd Font glyphs, from the Material Design icons
+    #[must_use]
+    pub fn glyphs() -> Self {
+        Self {
+            volume: IconMap::single("\u{f075f}")
+                .with_states(&[("muted", "\u{f075f}")])
+                .with_levels(&[
+                    (Some(0), "\u{f075f}"),
+                    (Some(33), "\u{f057f}"),
+                    (Some(67), "\u{f0580}"),
+                    (None, "\u{f057e}"),
+                ]),
+            monitor: IconMap::single("\u{f00e0}").with_levels(&[
+                (Some(33), "\u{f00de}"),
+                (Some(67), "\u{f00df}"),
+                (None, "\u{f00e0}"),
+            ]),
+            keyboard: IconMap::single("\u{f030c}").with_levels(&[(Some(0), "\u{f030e}"), (None, "\u{f030c}")]),
+            battery: IconMap {
+                states: HashMap::from([
+                    ("not_charging".to_string(), StateIcon::Name("\u{f0091}".to_string())),
+                    (
+                        "charging".to_string(),
+                        StateIcon::Levels(battery_levels(|step| BATTERY_CHARGING_GLYPHS[step].to_string())),
+                    ),
+                ]),
+                levels: battery_levels(|step| BATTERY_GLYPHS[step].to_string()),
+                default: "\u{f0091}".to_string(),
+            },
+            bluetooth: IconMap::single("\u{f00b2}").with_states(&[("on", "\u{f00af}"), ("off", "\u{f00b2}")]),
+            dnd: IconMap::single("\u{f009a}").with_states(&[("on", "\u{f009b}"), ("off", "\u{f009a}")]),
+            fan_profile: IconMap::single("\u{f0210}"),
+            ram: IconMap::single("\u{f035b}"),
+        }
+    }
+}
+
+/// Icon maps which replace some of a set's maps, used for glyphs so the maps which aren't given keep their glyphs
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct PartialIconMaps {
+    pub volume: Option<IconMap>,
+    pub monitor: Option<IconMap>,
+    pub keyboard: Option<IconMap>,
+    pub battery: Option<IconMap>,
+    pub bluetooth: Option<IconMap>,
+    pub dnd: Option<IconMap>,
+    pub fan_profile: Option<IconMap>,
+    pub ram: Option<IconMap>,
+}
+
+impl IconMaps {
+    #[must_use]
+    pub fn merge(self, partial: PartialIconMaps) -> Self {
+        Self {
+            volume: partial.volume.unwrap_or(self.volume),
+            monitor: partial.monitor.unwrap_or(self.monitor),
+            keyboard: partial.keyboard.unwrap_or(self.keyboard),
+            battery: partial.battery.unwrap_or(self.battery),
+            bluetooth: partial.bluetooth.unwrap_or(self.bluetooth),
+            dnd: partial.dnd.unwrap_or(self.dnd),
+            fan_profile: partial.fan_profile.unwrap_or(self.fan_profile),
+            ram: partial.ram.unwrap_or(self.ram),
+        }
+    }
+}
+
+// Battery glyphs for each ten percent, from empty to full
+const BATTERY_GLYPHS: &[&str] = &[
+    "\u{f008e}",
+    "\u{f007a}",
+    "\u{f007b}",
+    "\u{f007c}",
+    "\u{f007d}",
+    "\u{f007e}",
+    "\u{f007f}",
+    "\u{f0080}",
+    "\u{f0081}",
+    "\u{f0082}",
+    "\u{f0079}",
+];
+const BATTERY_CHARGING_GLYPHS: &[&str] = &[
+    "\u{f089f}",
+    "\u{f089c}",
+    "\u{f0086}",
+    "\u{f0087}",
+    "\u{f0088}",
+    "\u{f089d}",
+    "\u{f0089}",
+    "\u{f089e}",
+    "\u{f008a}",
+    "\u{f008b}",
+    "\u{f0085}",
+];
+
+/// Icons used for a value range, ``max`` is inclusive and a level without one matches every value
+#[derive(Deserialize, Debug, Clone)]
+pub struct IconLevel {
+    pub max: Option<u32>,
+    pub icon: String,
+}
+
+impl IconLevel {
+    #[must_use]
+    pub fn new(max: Option<u32>, icon: &str) -> Self {
+        Self {
+            max,
+            icon: icon.to_string(),
+        }
+    }
+}
+
+/// The icon of a state, either a single icon or icons for value ranges
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum StateIcon {
+    Name(String),
+    Levels(Vec<IconLevel>),
+}
+
+/// Mapping of a module's states and values to icon names
+///
+/// A state which is in ``states`` takes priority, then the first level which the value fits in is used
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct IconMap {
+    pub states: HashMap<String, StateIcon>,
+    pub levels: Vec<IconLevel>,
+    /// Used when no state or level matches
+    pub default: String,
+}
+
+impl IconMap {
+    /// A map with a single icon
+    #[must_use]
+    pub fn single(icon: &str) -> Self {
+        Self {
+            default: icon.to_string(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_states(mut self, states: &[(&str, &str)]) -> Self {
+        self.states.extend(
+            states
+                .iter()
+                .map(|(state, icon)| ((*state).to_string(), StateIcon::Name((*icon).to_string()))),
+        );
+
+        self
+    }
+
+    #[must_use]
+    pub fn with_levels(mut self, levels: &[(Option<u32>, &str)]) -> Self {
+        self.levels = to_levels(levels);
+
+        self
+    }
+
+    /// Finds the icon for a state and value
+    #[must_use]
+    pub fn get(&self, state: Option<&str>, value: u32) -> String {
+        let levels = match state.and_then(|state| self.states.get(state)) {
+            Some(StateIcon::Name(icon)) => return icon.clone(),
+            Some(StateIcon::Levels(levels)) => levels,
+            None => &self.levels,
+        };
+
+        levels
+            .iter()
+            .find(|level| level.max.is_none_or(|max| value <= max))
+            .map_or_else(|| self.default.clone(), |level| level.icon.clone())
+    }
+}
+
+fn to_levels(levels: &[(Option<u32>, &str)]) -> Vec<IconLevel> {
+    levels.iter().map(|(max, icon)| IconLevel::new(*max, icon)).collect()
+}
+
+// Battery icons come in steps of ten percent, such as ``battery-040`` and ``battery-040-charging``
+fn battery_levels(icon: impl Fn(usize) -> String) -> Vec<IconLevel> {
+    (0..=10)
+        .map(|step| IconLevel {
+            max: (step < 10).then_some(step as u32 * 10 + 9),
+            icon: icon(step),
+        })
+        .collect()
+}
+
+#[must_use]
+pub fn default_volume() -> IconMap {
+    IconMap::single("audio-volume-muted")
+        .with_states(&[("muted", "audio-volume-muted")])
+        .with_levels(&[
+            (Some(0), "audio-volume-muted"),
+            (Some(33), "audio-volume-low"),
+            (Some(67), "audio-volume-medium"),
+            (Some(100), "audio-volume-high"),
+            (None, "audio-volume-overamplified"),
+        ])
+}
+
+#[must_use]
+pub fn default_monitor() -> IconMap {
+    IconMap::single(&format!("display-brightness-high{ICON_END}")).with_levels(&[
+        (Some(0), &format!("display-brightness-off{ICON_END}")),
+        (Some(33), &format!("display-brightness-low{ICON_END}")),
+        (Some(67), &format!("display-brightness-medium{ICON_END}")),
+        (None, &format!("display-brightness-high{ICON_END}")),
+    ])
+}
+
+#[must_use]
+pub fn default_keyboard() -> IconMap {
+    IconMap::single(&format!("keyboard-brightness-high{ICON_END}")).with_levels(&[
+        (Some(0), &format!("keyboard-brightness-off{ICON_END}")),
+        (Some(33), &format!("keyboard-brightness-medium{ICON_END}")),
+        (Some(67), &format!("keyboard-brightness{ICON_END}")),
+        (None, &format!("keyboard-brightness-high{ICON_END}")),
+    ])
+}
+
+#[must_use]
+pub fn default_battery() -> IconMap {
+    IconMap {
+        states: HashMap::from([
+            (
+                "not_charging".to_string(),
+                StateIcon::Name("battery-missing".to_string()),
+            ),
+            (
+                "charging".to_string(),
+                StateIcon::Levels(battery_levels(|step| format!("battery-{:0>3}-charging", step * 10))),
+            ),
+        ]),
+        levels: battery_levels(|step| format!("battery-{:0>3}", step * 10)),
+        default: "battery-missing".to_string(),
+    }
+}
+
+#[must_use]
+pub fn default_bluetooth() -> IconMap {
+    IconMap::single(&format!("bluetooth-disabled{ICON_END}")).with_states(&[
+        ("on", &format!("bluetooth-active{ICON_END}")),
+        ("off", &format!("bluetooth-disabled{ICON_END}")),
+    ])
+}
+
+#[must_use]
+pub fn default_dnd() -> IconMap {
+    IconMap::single(&format!("notifications{ICON_END}")).with_states(&[
+        ("on", &format!("notifications-disabled{ICON_END}")),
+        ("off", &format!("notifications{ICON_END}")),
+    ])
+}
+
+#[must_use]
+pub fn default_fan_profile() -> IconMap {
+    IconMap::single(&format!("sensors-fan{ICON_END}"))
+}
+
+#[must_use]
+pub fn default_ram() -> IconMap {
+    IconMap::single(&format!("nvidia-ram{ICON_END}"))
+}
+
+/// Name of a bool state in the icon maps
+#[must_use]
+pub const fn on_off(state: bool) -> &'static str {
+    if state {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Tuples for an icon: its name with the configured extension, its file when resolving is on, and its glyph
+///
+/// The file is null when the icon is not found in the theme
+///
+/// The keys start with the prefix, such as ``keyboard_icon``, ``keyboard_icon_path`` and ``keyboard_glyph``
+#[must_use]
+pub fn to_tuples(prefix: &str, icon: &str, glyph: &str) -> Tuples {
+    let mut tuples = vec![(
+        format!("{prefix}icon"),
+        format!("{icon}{}", CONFIG.icons.extension).into(),
+    )];
+
+    if CONFIG.icons.resolve {
+        tuples.push((
+            format!("{prefix}icon_path"),
+            icon_theme::resolve(icon)
+                .map(|path| path.to_string_lossy().to_string())
+                .into(),
+        ));
+    }
+
+    tuples.push((format!("{prefix}glyph"), glyph.into()));
+
+    tuples
+}
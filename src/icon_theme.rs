@@ -0,0 +1,476 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{LazyLock, Mutex},
+};
+
+use crate::config::CONFIG;
+
+const FALLBACK_THEME: &str = "hicolor";
+const EXTENSIONS: &[&str] = &["svg", "png", "xpm"];
+
+// Resolved path of each icon name, None when the icon could not be found
+static PATHS: LazyLock<Mutex<HashMap<String, Option<PathBuf>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// Parsed index.theme of each theme, None when the theme is not installed
+static THEMES: LazyLock<Mutex<HashMap<String, Option<Theme>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirectoryType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+/// A directory of a theme, from its section in ``index.theme``
+#[derive(Debug, Clone)]
+struct ThemeDirectory {
+    path: String,
+    size: u32,
+    scale: u32,
+    kind: DirectoryType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDirectory {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+
+        match self.kind {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirectoryType::Threshold => self.size.abs_diff(size) <= self.threshold,
+        }
+    }
+
+    const fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let scaled = size * scale;
+
+        match self.kind {
+            DirectoryType::Fixed => (self.size * self.scale).abs_diff(scaled),
+            DirectoryType::Scalable => {
+                if scaled < self.min_size * self.scale {
+                    (self.min_size * self.scale).saturating_sub(scaled)
+                } else {
+                    scaled.saturating_sub(self.max_size * self.scale)
+                }
+            }
+            DirectoryType::Threshold => {
+                if scaled < self.size.saturating_sub(self.threshold) * self.scale {
+                    (self.min_size * self.scale).saturating_sub(scaled)
+                } else if scaled > (self.size + self.threshold) * self.scale {
+                    scaled.saturating_sub(self.max_size * self.scale)
+                } else {
+                    0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Theme {
+    /// Every base directory which has a directory for the theme
+    roots: Vec<PathBuf>,
+    inherits: Vec<String>,
+    directories: Vec<ThemeDirectory>,
+}
+
+impl Theme {
+    fn load(name: &str) -> Option<Self> {
+        Self::load_from(name, base_dirs())
+    }
+
+    // Loads the theme from the given base directories, searched in order
+    fn load_from(name: &str, base_dirs: Vec<PathBuf>) -> Option<Self> {
+        let roots = base_dirs
+            .into_iter()
+            .map(|dir| dir.join(name))
+            .filter(|dir| dir.is_dir())
+            .collect::<Vec<_>>();
+
+        // The first index.theme found is the one which is used
+        let index = roots
+            .iter()
+            .find_map(|root| std::fs::read_to_string(root.join("index.theme")).ok())?;
+
+        let sections = parse_index(&index);
+        let theme_section = sections.get("Icon Theme")?;
+
+        let list = |key: &str| {
+            theme_section
+                .get(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        };
+
+        let directories = list("Directories")
+            .into_iter()
+            .chain(list("ScaledDirectories"))
+            .filter_map(|path| {
+                let section = sections.get(path.as_str())?;
+                let number = |key: &str| section.get(key).and_then(|value| value.parse::<u32>().ok());
+
+                let size = number("Size")?;
+
+                Some(ThemeDirectory {
+                    size,
+                    scale: number("Scale").unwrap_or(1),
+                    kind: match section.get("Type").map(String::as_str) {
+                        Some("Fixed") => DirectoryType::Fixed,
+                        Some("Scalable") => DirectoryType::Scalable,
+                        _ => DirectoryType::Threshold,
+                    },
+                    min_size: number("MinSize").unwrap_or(size),
+                    max_size: number("MaxSize").unwrap_or(size),
+                    threshold: number("Threshold").unwrap_or(2),
+                    path,
+                })
+            })
+            .collect();
+
+        Some(Self {
+            roots,
+            inherits: list("Inherits"),
+            directories,
+        })
+    }
+
+    // Every file which the icon could be in a directory, across the base directories
+    fn files<'a>(&'a self, directory: &'a ThemeDirectory, name: &'a str) -> impl Iterator<Item = PathBuf> + 'a {
+        self.roots.iter().flat_map(move |root| {
+            EXTENSIONS
+                .iter()
+                .map(move |extension| root.join(&directory.path).join(format!("{name}.{extension}")))
+        })
+    }
+
+    /// Finds the icon in this theme only, preferring a directory of the exact size, then the closest size
+    fn lookup(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        if let Some(path) = self
+            .directories
+            .iter()
+            .filter(|directory| directory.matches_size(size, scale))
+            .flat_map(|directory| self.files(directory, name))
+            .find(|path| path.is_file())
+        {
+            return Some(path);
+        }
+
+        self.directories
+            .iter()
+            .filter_map(|directory| {
+                self.files(directory, name)
+                    .find(|path| path.is_file())
+                    .map(|path| (directory.size_distance(size, scale), path))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, path)| path)
+    }
+}
+
+// Sections of an index.theme, each mapping its keys to values
+fn parse_index(index: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = None;
+
+    for line in index.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current = Some(section.to_string());
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    sections
+}
+
+/// Directories which hold icon themes, in the order which they are searched
+fn base_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    home.map(|home| home.join(".icons"))
+        .into_iter()
+        .chain(data_home.map(|dir| dir.join("icons")))
+        .chain(data_dirs.split(':').map(|dir| Path::new(dir).join("icons")))
+        .collect()
+}
+
+fn with_theme<T>(name: &str, f: impl FnOnce(&Theme) -> Option<T>) -> Option<T> {
+    let mut themes = THEMES.lock().ok()?;
+
+    let theme = themes
+        .entry(name.to_string())
+        .or_insert_with(|| Theme::load(name))
+        .clone();
+
+    drop(themes);
+
+    f(theme.as_ref()?)
+}
+
+/// Searches the theme, then the themes which it inherits from, depth first
+fn lookup_in_theme(theme: &str, name: &str, size: u32, scale: u32, visited: &mut Vec<String>) -> Option<PathBuf> {
+    if visited.iter().any(|visited| visited == theme) {
+        return None;
+    }
+
+    visited.push(theme.to_string());
+
+    let (path, inherits) = with_theme(theme, |theme| {
+        Some((theme.lookup(name, size, scale), theme.inherits.clone()))
+    })?;
+
+    path.or_else(|| {
+        inherits
+            .iter()
+            .find_map(|parent| lookup_in_theme(parent, name, size, scale, visited))
+    })
+}
+
+// Icons which aren't in any theme can be loose files in the pixmaps directory
+fn lookup_unthemed(name: &str) -> Option<PathBuf> {
+    base_dirs()
+        .into_iter()
+        .chain(std::iter::once(PathBuf::from("/usr/share/pixmaps")))
+        .flat_map(|dir| {
+            EXTENSIONS
+                .iter()
+                .map(move |extension| dir.join(format!("{name}.{extension}")))
+        })
+        .find(|path| path.is_file())
+}
+
+// The name, then the full colour icon of a symbolic name, then more generic names, such as ``audio-volume``
+fn fallback_names(name: &str) -> Vec<String> {
+    let mut names = vec![name.to_string()];
+    let mut current = name.strip_suffix("-symbolic").unwrap_or(name).to_string();
+
+    if current != name {
+        names.push(current.clone());
+    }
+
+    while let Some((generic, _)) = current.rsplit_once('-') {
+        current = generic.to_string();
+        names.push(current.clone());
+    }
+
+    names
+}
+
+fn resolve_uncached(name: &str) -> Option<PathBuf> {
+    let theme = CONFIG.icons.theme.as_str();
+    let (size, scale) = (CONFIG.icons.size, CONFIG.icons.scale);
+
+    fallback_names(name).iter().find_map(|name| {
+        lookup_in_theme(theme, name, size, scale, &mut Vec::new())
+            .or_else(|| lookup_in_theme(FALLBACK_THEME, name, size, scale, &mut Vec::new()))
+            .or_else(|| lookup_unthemed(name))
+    })
+}
+
+/// Finds the file of an icon name in the configured theme, following the freedesktop icon theme spec
+///
+/// Results are cached, so each name is only searched for once
+#[must_use]
+pub fn resolve(name: &str) -> Option<PathBuf> {
+    if let Some(path) = PATHS.lock().ok().and_then(|paths| paths.get(name).cloned()) {
+        return path;
+    }
+
+    let path = resolve_uncached(name);
+
+    if let Ok(mut paths) = PATHS.lock() {
+        paths.insert(name.to_string(), path.clone());
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = "
+[Icon Theme]
+Name=Test
+Inherits=hicolor
+Directories=16x16/status,48x48/status,scalable/status
+
+# Sizes of each directory
+[16x16/status]
+Size=16
+Type=Fixed
+
+[48x48/status]
+Size=48
+Type=Threshold
+
+[scalable/status]
+Size=64
+MinSize=8
+MaxSize=512
+Type=Scalable
+";
+
+    // A base directory holding a theme with the given icon files, which is removed when dropped
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        fn new(test: &str, files: &[&str]) -> Self {
+            let base_dir = std::env::temp_dir().join(format!("bar_daemon_icon_theme_{}_{test}", std::process::id()));
+            let theme_dir = base_dir.join("test");
+
+            for file in files.iter().copied().chain(["index.theme"]) {
+                let path = theme_dir.join(file);
+
+                if let Some(dir) = path.parent() {
+                    assert!(std::fs::create_dir_all(dir).is_ok(), "{}", dir.display());
+                }
+
+                let contents = if file == "index.theme" { INDEX } else { "" };
+                assert!(std::fs::write(&path, contents).is_ok(), "{}", path.display());
+            }
+
+            Self(base_dir)
+        }
+
+        fn theme(&self) -> Option<Theme> {
+            Theme::load_from("test", vec![self.0.clone()])
+        }
+
+        fn path(&self, file: &str) -> PathBuf {
+            self.0.join("test").join(file)
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn loads_theme() {
+        let fixture = Fixture::new("loads_theme", &[]);
+        let Some(theme) = fixture.theme() else {
+            panic!("The theme should load");
+        };
+
+        assert_eq!(theme.roots, [fixture.0.join("test")]);
+        assert_eq!(theme.inherits, ["hicolor"]);
+        assert_eq!(
+            theme
+                .directories
+                .iter()
+                .map(|directory| (
+                    directory.path.as_str(),
+                    directory.size,
+                    directory.min_size,
+                    directory.max_size
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("16x16/status", 16, 16, 16),
+                ("48x48/status", 48, 48, 48),
+                ("scalable/status", 64, 8, 512)
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_theme() {
+        let fixture = Fixture::new("missing_theme", &[]);
+
+        assert!(Theme::load_from("missing", vec![fixture.0.clone()]).is_none());
+    }
+
+    #[test]
+    fn looks_up_exact_size() {
+        let fixture = Fixture::new(
+            "looks_up_exact_size",
+            &["16x16/status/battery.png", "48x48/status/battery.svg"],
+        );
+        let theme = fixture.theme();
+
+        assert_eq!(
+            theme.as_ref().and_then(|theme| theme.lookup("battery", 16, 1)),
+            Some(fixture.path("16x16/status/battery.png"))
+        );
+        assert_eq!(
+            theme.as_ref().and_then(|theme| theme.lookup("battery", 47, 1)),
+            Some(fixture.path("48x48/status/battery.svg"))
+        );
+    }
+
+    #[test]
+    fn looks_up_closest_size() {
+        let fixture = Fixture::new(
+            "looks_up_closest_size",
+            &["16x16/status/bluetooth.png", "48x48/status/bluetooth.png"],
+        );
+        let theme = fixture.theme();
+
+        assert_eq!(
+            theme.as_ref().and_then(|theme| theme.lookup("bluetooth", 24, 1)),
+            Some(fixture.path("16x16/status/bluetooth.png"))
+        );
+        assert_eq!(
+            theme.as_ref().and_then(|theme| theme.lookup("bluetooth", 40, 1)),
+            Some(fixture.path("48x48/status/bluetooth.png"))
+        );
+    }
+
+    #[test]
+    fn looks_up_scalable() {
+        let fixture = Fixture::new("looks_up_scalable", &["scalable/status/volume.svg"]);
+        let theme = fixture.theme();
+
+        assert_eq!(
+            theme.as_ref().and_then(|theme| theme.lookup("volume", 128, 1)),
+            Some(fixture.path("scalable/status/volume.svg"))
+        );
+        assert_eq!(theme.as_ref().and_then(|theme| theme.lookup("missing", 128, 1)), None);
+    }
+
+    #[test]
+    fn falls_back_to_generic_names() {
+        assert_eq!(
+            fallback_names("audio-volume-high-symbolic"),
+            [
+                "audio-volume-high-symbolic",
+                "audio-volume-high",
+                "audio-volume",
+                "audio"
+            ]
+        );
+    }
+}
@@ -1,19 +1,116 @@
-use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::error::DaemonError;
+use serde_json::{Map, Value};
 
+use crate::{
+    config::CONFIG,
+    error::DaemonError,
+    tuples::{AllTuples, TupleValue},
+};
+
+// Set by ``--string-values``, which works like ``string_values`` in the config
+static STRING_VALUES: AtomicBool = AtomicBool::new(false);
+
+/// Writes every value as a string, even when ``string_values`` isn't set in the config
+pub fn set_string_values() {
+    STRING_VALUES.store(true, Ordering::Relaxed);
+}
+
+/// Whether values are written as strings, from the config or ``--string-values``
+#[must_use]
+pub fn string_values() -> bool {
+    CONFIG.json.string_values || STRING_VALUES.load(Ordering::Relaxed)
+}
+
+/// The tuples as JSON, with the modules and their keys in the order of the tuples, or sorted if ``sort_keys`` is set
+///
+/// The same tuples always give the same line, so clients can skip lines which haven't changed
+///
 /// # Errors
-/// Returns an error if the generated hashmap can't be converted into a JSON
-pub fn tuples_to_json(tuples: Vec<(String, Vec<(String, String)>)>) -> Result<String, DaemonError> {
-    // Convert tuples nested hashmap
-    let mut json_map: HashMap<String, HashMap<String, String>> = HashMap::new();
+/// Returns an error if the generated map can't be converted into a JSON
+pub fn tuples_to_json(tuples: AllTuples) -> Result<String, DaemonError> {
+    let json_map = to_map(tuples, string_values(), CONFIG.json.sort_keys);
+
+    Ok(serde_json::to_string(&json_map)?)
+}
+
+/// A module's tuples as a JSON object, in the same order as ``tuples_to_json``
+#[must_use]
+pub fn tuples_to_object(tuples: &[(String, TupleValue)]) -> Map<String, Value> {
+    to_object(tuples, string_values(), CONFIG.json.sort_keys)
+}
 
-    let tuples_locked = tuples;
-    for (group, pairs) in tuples_locked {
-        let inner_map = pairs.into_iter().collect::<HashMap<_, _>>();
+fn to_map(tuples: AllTuples, string_values: bool, sort_keys: bool) -> Map<String, Value> {
+    let mut json_map = tuples
+        .into_iter()
+        .map(|(group, pairs)| (group, Value::Object(to_object(&pairs, string_values, sort_keys))))
+        .collect::<Map<_, _>>();
 
-        json_map.insert(group, inner_map);
+    if sort_keys {
+        json_map.sort_keys();
     }
 
-    Ok(serde_json::to_string(&json_map)?)
+    json_map
+}
+
+fn to_object(tuples: &[(String, TupleValue)], string_values: bool, sort_keys: bool) -> Map<String, Value> {
+    let mut object = tuples
+        .iter()
+        .map(|(key, value)| (key.clone(), value.to_json(string_values)))
+        .collect::<Map<_, _>>();
+
+    if sort_keys {
+        object.sort_keys();
+    }
+
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuples() -> AllTuples {
+        vec![
+            (
+                "volume".to_string(),
+                vec![
+                    ("percent".to_string(), 40_u32.into()),
+                    ("mute_state".to_string(), false.into()),
+                    ("icon".to_string(), "audio-volume-medium".into()),
+                ],
+            ),
+            (
+                "battery".to_string(),
+                vec![
+                    ("time".to_string(), TupleValue::Null),
+                    ("percent".to_string(), 80_u32.into()),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn keeps_order() {
+        assert_eq!(
+            Value::Object(to_map(tuples(), false, false)).to_string(),
+            r#"{"volume":{"percent":40,"mute_state":false,"icon":"audio-volume-medium"},"battery":{"time":null,"percent":80}}"#
+        );
+    }
+
+    #[test]
+    fn sorts_keys() {
+        assert_eq!(
+            Value::Object(to_map(tuples(), false, true)).to_string(),
+            r#"{"battery":{"percent":80,"time":null},"volume":{"icon":"audio-volume-medium","mute_state":false,"percent":40}}"#
+        );
+    }
+
+    #[test]
+    fn writes_string_values() {
+        assert_eq!(
+            Value::Object(to_object(&tuples()[1].1, true, false)).to_string(),
+            r#"{"time":"","percent":"80"}"#
+        );
+    }
 }
@@ -1,16 +1,19 @@
 use std::{collections::HashMap, path::Path, sync::Arc};
 
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    io::{AsyncBufReadExt, BufReader},
     net::UnixStream,
     sync::{mpsc, Mutex, Notify},
 };
 use uuid::Uuid;
 
 use crate::{
-    daemon::{DaemonMessage, SOCKET_PATH},
+    daemon::{write_message, DaemonMessage, SOCKET_PATH},
     error::DaemonError,
+    i3bar,
     json::tuples_to_json,
+    output::OutputMode,
+    state::State,
     tuples::{get_all_tuples, tuple_name_to_tuples, TupleName, TUPLE_NAMES},
     POLLING_RATE,
 };
@@ -20,6 +23,7 @@ pub struct Client {
     pub stream: UnixStream,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ClientMessage {
     UpdateVolume,
     UpdateBrightness,
@@ -27,6 +31,7 @@ pub enum ClientMessage {
     UpdateBattery,
     UpdateRam,
     UpdateFanProfile,
+    UpdateDnd,
     UpdateAll,
 }
 
@@ -36,7 +41,8 @@ pub enum ClientMessage {
 /// Returns an error if ``DaemonMessage`` could not be created from bytes
 /// Returns an error if socket cannot be read
 /// Returns an error if socket could not be wrote to
-pub async fn listen() -> Result<(), DaemonError> {
+/// Returns an error if a line from the daemon cannot be formatted
+pub async fn listen(output: &OutputMode) -> Result<(), DaemonError> {
     if !Path::new(SOCKET_PATH).exists() {
         eprintln!("Socket not found. Is the daemon running?");
         return Ok(());
@@ -45,18 +51,25 @@ pub async fn listen() -> Result<(), DaemonError> {
     let mut stream = UnixStream::connect(SOCKET_PATH).await?;
 
     // Tell the daemon that this client wants to listen
-    stream.write_all(&postcard::to_stdvec(&DaemonMessage::Listen)?).await?;
+    write_message(&mut stream, &DaemonMessage::Listen).await?;
 
-    // Get the initial tuples, as JSON, and write to stdout
-    let json = tuples_to_json(get_all_tuples().await?)?;
-    println!("{json}");
+    if matches!(output, OutputMode::I3bar) {
+        println!("{}\n[", i3bar::HEADER);
 
-    // Read the lines which the client sends
+        // Clicks on the blocks are sent on stdin while the updates are printed
+        tokio::spawn(async {
+            if let Err(e) = i3bar::read_clicks().await {
+                eprintln!("Could not read click events:\n\t{e}");
+            }
+        });
+    }
+
+    // Read the lines which the client sends, starting with the initial tuples
     let reader = BufReader::new(stream);
     let mut lines = reader.lines();
 
     while let Ok(Some(line)) = lines.next_line().await {
-        println!("{line}");
+        println!("{}", output.line_to_string(&line)?);
     }
 
     Ok(())
@@ -85,6 +98,11 @@ pub async fn handle_clients(
                     continue;
                 };
 
+                // Remember the new values, so they can be restored after a restart
+                if let Err(e) = State::queue_update(client_message) {
+                    eprintln!("Could not save state:\n\t{e}");
+                }
+
                 let clients_empty = clients.lock().await.is_empty();
 
                 if !clients_empty {
@@ -100,6 +118,7 @@ pub async fn handle_clients(
                             ClientMessage::UpdateBattery => TupleName::Battery,
                             ClientMessage::UpdateRam => TupleName::Ram,
                             ClientMessage::UpdateFanProfile => TupleName::FanProfile,
+                            ClientMessage::UpdateDnd => TupleName::Dnd,
                             ClientMessage::UpdateAll => unreachable!(),
                         };
 
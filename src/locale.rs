@@ -0,0 +1,60 @@
+use std::{collections::HashMap, sync::LazyLock};
+
+use crate::{
+    config::{Config, CONFIG},
+    error::DaemonError,
+};
+
+pub const LOCALE_DIR_NAME: &str = "locales";
+
+// Translations for the current locale, keyed by the English text
+static CATALOG: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+    load().unwrap_or_else(|e| {
+        eprintln!("Could not load translations:\n\t{e}");
+
+        HashMap::new()
+    })
+});
+
+/// The locale from the config, or from the environment in the same order as gettext
+#[must_use]
+pub fn get_locale() -> Option<String> {
+    CONFIG.locale.clone().or_else(|| {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(std::env::var_os)
+            .map(|locale| locale.to_string_lossy().to_string())
+            .find(|locale| !locale.is_empty())
+    })
+}
+
+/// Loads the catalog for the locale, trying ``de_DE`` and then ``de`` for ``de_DE.UTF-8``
+///
+/// # Errors
+/// Returns an error if the catalog exists but cannot be read
+/// Returns an error if the catalog is not valid TOML
+pub fn load() -> Result<HashMap<String, String>, DaemonError> {
+    let (Some(locale), Some(config_dir)) = (get_locale(), Config::dir()) else {
+        return Ok(HashMap::new());
+    };
+
+    // Remove the encoding and modifier
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let language = locale.split('_').next().unwrap_or_default();
+
+    for name in [locale, language] {
+        let path = config_dir.join(LOCALE_DIR_NAME).join(format!("{name}.toml"));
+
+        if path.exists() {
+            return Ok(toml::from_str(&std::fs::read_to_string(path)?)?);
+        }
+    }
+
+    Ok(HashMap::new())
+}
+
+/// Translates the text into the current locale, returning it unchanged when there is no translation
+#[must_use]
+pub fn translate(text: &str) -> String {
+    CATALOG.get(text).cloned().unwrap_or_else(|| text.to_string())
+}
@@ -1,32 +1,183 @@
-// const SLOPE: f64 = 0.5;
+use serde::Deserialize;
 
-#[must_use]
-pub fn linear_to_logarithmic(linear_percent: f64) -> f64 {
-    if linear_percent <= 0.0 {
-        return 0.0;
+/// Maps the linear percentage which is shown to the user onto the percentage which is given to the device
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "CurveConfig")]
+pub enum Curve {
+    Linear,
+    /// Exponent < 1.0 = gentler curve, spreads out low values
+    Power {
+        exponent: f64,
+    },
+    /// The cubic mapping which Pipewire uses for its own volume sliders
+    Cubic,
+    /// Each percent is an equal step in decibels, over a range of ``range`` dB
+    Decibel {
+        range: f64,
+    },
+}
+
+// The curve as it is written in the config, before its parameters are checked
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum CurveConfig {
+    Linear,
+    Power { exponent: f64 },
+    Cubic,
+    Decibel { range: f64 },
+}
+
+impl TryFrom<CurveConfig> for Curve {
+    type Error = String;
+
+    // Parameters which aren't positive would map the percentages onto infinity or NaN
+    fn try_from(value: CurveConfig) -> Result<Self, Self::Error> {
+        match value {
+            CurveConfig::Linear => Ok(Self::Linear),
+            CurveConfig::Cubic => Ok(Self::Cubic),
+            CurveConfig::Power { exponent } if exponent.is_finite() && exponent > 0.0 => Ok(Self::Power { exponent }),
+            CurveConfig::Power { exponent } => Err(format!("Invalid exponent '{exponent}'. Use a number above 0.")),
+            CurveConfig::Decibel { range } if range.is_finite() && range > 0.0 => Ok(Self::Decibel { range }),
+            CurveConfig::Decibel { range } => Err(format!("Invalid range '{range}'. Use a number of dB above 0.")),
+        }
+    }
+}
+
+impl Curve {
+    /// Percentages above 100, from boosting the volume, are passed on linearly,
+    /// since the curves grow far past their range above 100
+    #[must_use]
+    pub fn linear_to_device(self, linear_percent: f64) -> f64 {
+        if linear_percent <= 0.0 {
+            return 0.0;
+        }
+
+        if linear_percent > 100.0 {
+            return linear_percent;
+        }
+
+        let normalized = linear_percent / 100.0;
+
+        100.0
+            * match self {
+                Self::Linear => normalized,
+                Self::Power { exponent } => normalized.powf(exponent),
+                Self::Cubic => normalized.powi(3),
+                Self::Decibel { range } => 10_f64.powf((normalized - 1.0) * range / 20.0),
+            }
     }
 
-    if linear_percent >= 100.0 {
-        return 100.0;
+    #[must_use]
+    pub fn device_to_linear(self, device_percent: f64) -> f64 {
+        if device_percent <= 0.0 {
+            return 0.0;
+        }
+
+        if device_percent > 100.0 {
+            return device_percent;
+        }
+
+        let normalized = device_percent / 100.0;
+
+        100.0
+            * match self {
+                Self::Linear => normalized,
+                Self::Power { exponent } => normalized.powf(1.0 / exponent),
+                Self::Cubic => normalized.cbrt(),
+                // Values quieter than the range are treated as silent
+                Self::Decibel { range } => (1.0 + 20.0 * normalized.log10() / range).max(0.0),
+            }
     }
 
-    // Slope < 1.0 = gentler curve, spreads out low values
-    let normalized = linear_percent / 100.0;
-    // 100.0 * (normalized.powf(SLOPE))
-    100.0 * (normalized.sqrt())
+    /// Converts a device percentage back into the nearest whole linear percentage
+    #[must_use]
+    pub fn device_to_linear_percent(self, device_percent: f64) -> u32 {
+        self.device_to_linear(device_percent).round() as u32
+    }
 }
 
-#[must_use]
-pub fn logarithmic_to_linear(log_percent: f64) -> f64 {
-    if log_percent <= 0.0 {
-        return 0.0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 5] = [
+        Curve::Linear,
+        Curve::Power { exponent: 0.5 },
+        Curve::Power { exponent: 2.0 },
+        Curve::Cubic,
+        Curve::Decibel { range: 60.0 },
+    ];
+
+    #[test]
+    fn round_trip() {
+        for curve in CURVES {
+            for percent in 0..=100 {
+                let device_percent = curve.linear_to_device(f64::from(percent));
+
+                assert_eq!(curve.device_to_linear_percent(device_percent), percent, "{curve:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_from_whole_device_values() {
+        // A backlight with 255 steps only takes whole values, so the set value is rounded before it is read back
+        for curve in [Curve::Linear, Curve::Power { exponent: 0.5 }] {
+            let max_value = 255.0;
+            let value = (curve.linear_to_device(37.0) / 100.0 * max_value).round();
+
+            assert_eq!(
+                curve.device_to_linear_percent(value / max_value * 100.0),
+                37,
+                "{curve:?}"
+            );
+        }
     }
 
-    if log_percent >= 100.0 {
-        return 100.0;
+    #[test]
+    fn boost_stays_within_max_percent() {
+        let max_percent = 150;
+
+        for curve in CURVES {
+            for percent in 100..=max_percent {
+                let device_percent = curve.linear_to_device(f64::from(percent));
+
+                assert!(device_percent <= f64::from(max_percent), "{curve:?} {percent}");
+                assert_eq!(curve.device_to_linear_percent(device_percent), percent, "{curve:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn device_range() {
+        for curve in CURVES {
+            assert!(curve.linear_to_device(0.0).abs() < f64::EPSILON, "{curve:?}");
+            assert!((curve.linear_to_device(100.0) - 100.0).abs() < 1e-9, "{curve:?}");
+        }
     }
 
-    let normalized = log_percent / 100.0;
-    // 100.0 * normalized.powf(1.0 / SLOPE)
-    100.0 * normalized.powi(2)
+    #[test]
+    fn deserialize() {
+        assert_eq!(
+            toml::from_str("type = \"power\"\nexponent = 0.5").ok(),
+            Some(Curve::Power { exponent: 0.5 })
+        );
+        assert_eq!(
+            toml::from_str("type = \"decibel\"\nrange = 60.0").ok(),
+            Some(Curve::Decibel { range: 60.0 })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        for config in [
+            "type = \"power\"\nexponent = 0.0",
+            "type = \"power\"\nexponent = -1.0",
+            "type = \"power\"\nexponent = nan",
+            "type = \"decibel\"\nrange = 0.0",
+            "type = \"decibel\"\nrange = -60.0",
+        ] {
+            assert!(toml::from_str::<Curve>(config).is_err(), "{config}");
+        }
+    }
 }
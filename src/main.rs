@@ -16,20 +16,32 @@ pub mod battery;
 pub mod bluetooth;
 pub mod brightness;
 pub mod cli;
+pub mod coalesce;
 pub mod command;
+pub mod config;
 pub mod daemon;
+pub mod dnd;
 pub mod error;
+pub mod fade;
 pub mod fan_profile;
+pub mod i3bar;
+pub mod icon;
+pub mod icon_theme;
 pub mod json;
 pub mod listener;
+pub mod locale;
 pub mod log_linear;
+pub mod notification;
+pub mod output;
 pub mod ram;
+pub mod schedule;
 pub mod shutdown;
+pub mod state;
 pub mod tuples;
 pub mod volume;
+pub mod waybar;
 
 pub const ICON_END: &str = "-symbolic";
-pub const ICON_EXT: &str = ""; // ".svg"
 
 pub const NOTIFICATION_ID: u32 = 42069;
 pub const NOTIFICATION_TIMEOUT: u32 = 1000;
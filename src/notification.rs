@@ -0,0 +1,766 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use zbus::{blocking::Connection, names::MemberName, zvariant::Value, MatchRule, MessageStream};
+
+use crate::{
+    cli::parse_set_message, command, config::CONFIG, daemon::apply_set_message, dnd::Dnd, error::DaemonError,
+    listener::ClientMessage, locale::translate, NOTIFICATION_ID, NOTIFICATION_TIMEOUT,
+};
+
+pub const APP_NAME: &str = "bar_daemon";
+
+const DBUS_DESTINATION: &str = "org.freedesktop.Notifications";
+const DBUS_PATH: &str = "/org/freedesktop/Notifications";
+const DBUS_INTERFACE: &str = "org.freedesktop.Notifications";
+
+// Session bus connection, opened on first use and dropped if it stops working
+//
+// Notifications are sent and their signals are received on the same connection, as servers may only send
+// ``ActionInvoked`` to the connection which made the notification
+static CONNECTION: LazyLock<Mutex<Option<zbus::Connection>>> = LazyLock::new(|| Mutex::new(None));
+
+// The id which the notification server gave to each replace id, so that servers which pick their own ids still replace
+static SERVER_IDS: LazyLock<Mutex<HashMap<u32, u32>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// When a notification was last shown, and the latest notification which is waiting to be shown after it
+type Debounce = (Instant, Option<Notification>);
+
+// Debounce state for each replace id
+static DEBOUNCE: LazyLock<Mutex<HashMap<u32, Debounce>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// The actions of each notification which is being shown, keyed by the id which the server gave it
+static ACTIONS: LazyLock<Mutex<HashMap<u32, Vec<NotificationAction>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// How notifications are shown
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Notifier {
+    /// The ``org.freedesktop.Notifications`` service on the session bus
+    Dbus,
+    /// Spawns ``dunstify`` for each notification
+    Dunstify,
+    /// Notifications are not shown
+    None,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Urgency {
+    Low = 0,
+    Normal = 1,
+    Critical = 2,
+}
+
+impl Urgency {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+/// A button on a notification, which either runs a set command or a shell command when it is chosen
+#[derive(Deserialize, Debug, Clone)]
+pub struct NotificationAction {
+    pub label: String,
+    /// Arguments of a set command, such as ``bluetooth state false``
+    pub set: Option<String>,
+    /// Ran with ``sh -c``
+    pub command: Option<String>,
+}
+
+impl NotificationAction {
+    #[must_use]
+    pub fn set(label: &str, set: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            set: Some(set.to_string()),
+            command: None,
+        }
+    }
+
+    #[must_use]
+    pub fn command(label: &str, command: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            set: None,
+            command: Some(command.to_string()),
+        }
+    }
+
+    /// # Errors
+    /// Returns an error if the set command is not valid, or cannot be applied
+    /// Returns an error if the shell command cannot be spawned
+    pub fn run(&self, clients_tx: &mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+        if let Some(set) = &self.set {
+            // Errors of queued requests are replied rather than returned
+            apply_set_message(parse_set_message(set)?, clients_tx)?.into_result()?;
+        }
+
+        if let Some(shell_command) = &self.command {
+            command::run("sh", &["-c", shell_command.as_str()])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Settings for the notifications of one module
+#[derive(Debug, Clone)]
+pub struct NotificationChannel {
+    pub enabled: bool,
+    /// Notifications with the same replace id replace each other
+    pub replace_id: u32,
+    pub urgency: Urgency,
+    /// Milliseconds before the notification closes, 0 keeps it open
+    pub timeout: u32,
+    /// Replaces the default summary, placeholders like ``{percent}`` are filled in
+    pub summary: Option<String>,
+    /// Body shown below the summary, with the same placeholders
+    pub body: Option<String>,
+    /// Buttons on the notification, only shown by the D-Bus backend
+    pub actions: Vec<NotificationAction>,
+}
+
+impl Default for NotificationChannel {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            replace_id: NOTIFICATION_ID,
+            urgency: Urgency::Normal,
+            timeout: NOTIFICATION_TIMEOUT,
+            summary: None,
+            body: None,
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// A channel from the config file, where missing settings keep the module's defaults
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct PartialNotificationChannel {
+    pub enabled: Option<bool>,
+    pub replace_id: Option<u32>,
+    pub urgency: Option<Urgency>,
+    pub timeout: Option<u32>,
+    pub summary: Option<String>,
+    pub body: Option<String>,
+    pub actions: Option<Vec<NotificationAction>>,
+}
+
+impl NotificationChannel {
+    #[must_use]
+    pub fn with_replace_id(replace_id: u32) -> Self {
+        Self {
+            replace_id,
+            ..Self::default()
+        }
+    }
+
+    /// Replaces the settings which are given in ``partial``
+    #[must_use]
+    pub fn merge(self, partial: PartialNotificationChannel) -> Self {
+        Self {
+            enabled: partial.enabled.unwrap_or(self.enabled),
+            replace_id: partial.replace_id.unwrap_or(self.replace_id),
+            urgency: partial.urgency.unwrap_or(self.urgency),
+            timeout: partial.timeout.unwrap_or(self.timeout),
+            summary: partial.summary.or(self.summary),
+            body: partial.body.or(self.body),
+            actions: partial.actions.unwrap_or(self.actions),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    pub icon: String,
+    pub urgency: Urgency,
+    pub replace_id: u32,
+    pub timeout: u32,
+    /// Percentage shown in a progress bar
+    pub value: Option<u32>,
+    pub actions: Vec<NotificationAction>,
+    enabled: bool,
+}
+
+impl Notification {
+    /// Creates a notification using the settings of ``channel``, its summary template is used over ``summary``
+    ///
+    /// The default summary and the placeholder values are translated into the current locale
+    #[must_use]
+    pub fn new(channel: &NotificationChannel, summary: &str, icon: &str, placeholders: &[(&str, &str)]) -> Self {
+        let placeholders = placeholders
+            .iter()
+            .map(|(name, value)| (*name, translate(value)))
+            .collect::<Vec<_>>();
+
+        Self {
+            summary: render(
+                channel.summary.clone().unwrap_or_else(|| translate(summary)).as_str(),
+                &placeholders,
+            ),
+            body: channel
+                .body
+                .as_deref()
+                .map(|body| render(body, &placeholders))
+                .unwrap_or_default(),
+            icon: icon.trim().to_string(),
+            urgency: channel.urgency,
+            replace_id: channel.replace_id,
+            timeout: channel.timeout,
+            value: None,
+            actions: channel.actions.clone(),
+            enabled: channel.enabled,
+        }
+    }
+
+    #[must_use]
+    pub const fn value(mut self, value: u32) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Shows the notification using the backend from the config, unless its channel is disabled or do not disturb is on
+    ///
+    /// # Errors
+    /// Returns an error if the notification could not be shown
+    pub fn send(&self) -> Result<(), DaemonError> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        if self.hidden_by_dnd(Dnd::is_enabled()) {
+            return Ok(());
+        }
+
+        let interval = Duration::from_millis(CONFIG.notifications.debounce);
+
+        // Critical notifications are never held back
+        if interval.is_zero() || self.is_critical() {
+            return CONFIG.notifications.backend.send(self);
+        }
+
+        let mut debounce = DEBOUNCE.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+        if let Some((last_sent, pending)) = debounce.get_mut(&self.replace_id) {
+            let elapsed = last_sent.elapsed();
+
+            if elapsed < interval {
+                // Only the latest notification is kept, and a thread is already waiting if one was pending
+                if pending.replace(self.clone()).is_none() {
+                    let replace_id = self.replace_id;
+
+                    std::thread::spawn(move || {
+                        std::thread::sleep(interval.saturating_sub(elapsed));
+
+                        if let Err(e) = Self::send_pending(replace_id) {
+                            eprintln!("Notification could not be shown:\n\t{e}");
+                        }
+                    });
+                }
+
+                return Ok(());
+            }
+        }
+
+        debounce.insert(self.replace_id, (Instant::now(), None));
+        drop(debounce);
+
+        CONFIG.notifications.backend.send(self)
+    }
+
+    fn send_pending(replace_id: u32) -> Result<(), DaemonError> {
+        let pending = DEBOUNCE
+            .lock()
+            .map_err(|_| DaemonError::MutexLockError)?
+            .insert(replace_id, (Instant::now(), None))
+            .and_then(|(_, pending)| pending);
+
+        pending.map_or(Ok(()), |notification| CONFIG.notifications.backend.send(&notification))
+    }
+
+    // Do not disturb still lets critical notifications through
+    const fn hidden_by_dnd(&self, dnd_enabled: bool) -> bool {
+        dnd_enabled && !self.is_critical()
+    }
+
+    const fn is_critical(&self) -> bool {
+        matches!(self.urgency, Urgency::Critical)
+    }
+
+    // Critical notifications are never replaced, so a routine notification can't hide them
+    const fn replaces(&self) -> bool {
+        !self.is_critical()
+    }
+}
+
+/// Fills in each ``{name}`` in the template with its value
+#[must_use]
+pub fn render<S: AsRef<str>>(template: &str, placeholders: &[(&str, S)]) -> String {
+    placeholders.iter().fold(template.to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value.as_ref())
+    })
+}
+
+impl Notifier {
+    /// # Errors
+    /// Returns an error if the session bus cannot be connected to
+    /// Returns an error if the notification server rejects the notification
+    /// Returns an error if ``dunstify`` cannot be spawned
+    pub fn send(self, notification: &Notification) -> Result<(), DaemonError> {
+        match self {
+            Self::Dbus => Self::send_dbus(notification),
+            Self::Dunstify => Self::send_dunstify(notification),
+            Self::None => Ok(()),
+        }
+    }
+
+    fn send_dbus(notification: &Notification) -> Result<(), DaemonError> {
+        let mut connection = CONNECTION.lock().map_err(|_| DaemonError::MutexLockError)?;
+        if connection.is_none() {
+            *connection = Some(Connection::session()?.into_inner());
+        }
+
+        let Some(current_connection) = connection.clone().map(Connection::from) else {
+            return Ok(());
+        };
+
+        if let Err(e) = Self::send_dbus_on(&current_connection, notification) {
+            // Reconnect on the next notification, in case the bus has restarted
+            if matches!(e, DaemonError::DbusError(_)) {
+                *connection = None;
+            }
+
+            return Err(e);
+        }
+
+        drop(connection);
+
+        Ok(())
+    }
+
+    // Calls ``Notify`` on the connection, remembering the id and the actions which the server replies with
+    fn send_dbus_on(connection: &Connection, notification: &Notification) -> Result<(), DaemonError> {
+        let mut hints = HashMap::new();
+        hints.insert("urgency", Value::U8(notification.urgency as u8));
+        if let Some(value) = notification.value {
+            hints.insert("value", Value::I32(i32::try_from(value)?));
+        }
+
+        let replaces_id = if notification.replaces() {
+            SERVER_IDS
+                .lock()
+                .map_err(|_| DaemonError::MutexLockError)?
+                .get(&notification.replace_id)
+                .copied()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        // Actions are given as pairs of keys and labels, the key is the index of the action
+        let action_keys = (0..notification.actions.len())
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+        let actions = action_keys
+            .iter()
+            .zip(&notification.actions)
+            .flat_map(|(key, action)| [key.as_str(), action.label.as_str()])
+            .collect::<Vec<_>>();
+
+        let reply = connection.call_method(
+            Some(DBUS_DESTINATION),
+            DBUS_PATH,
+            Some(DBUS_INTERFACE),
+            "Notify",
+            &(
+                APP_NAME,
+                replaces_id,
+                notification.icon.as_str(),
+                notification.summary.as_str(),
+                notification.body.as_str(),
+                actions,
+                hints,
+                i32::try_from(notification.timeout)?,
+            ),
+        );
+
+        let id: u32 = reply?.body().deserialize()?;
+
+        if notification.replaces() {
+            SERVER_IDS
+                .lock()
+                .map_err(|_| DaemonError::MutexLockError)?
+                .insert(notification.replace_id, id);
+        }
+
+        // Remember the actions, so they can be ran when the server says one was chosen
+        let mut actions = ACTIONS.lock().map_err(|_| DaemonError::MutexLockError)?;
+        if notification.actions.is_empty() {
+            actions.remove(&id);
+        } else {
+            actions.insert(id, notification.actions.clone());
+        }
+        drop(actions);
+
+        Ok(())
+    }
+
+    fn send_dunstify(notification: &Notification) -> Result<(), DaemonError> {
+        let replace_id = notification.replace_id.to_string();
+        let timeout = notification.timeout.to_string();
+
+        let mut args = vec![
+            "-u",
+            notification.urgency.as_str(),
+            "-i",
+            notification.icon.as_str(),
+            "-t",
+            timeout.as_str(),
+        ];
+
+        if notification.replaces() {
+            args.extend(["-r", replace_id.as_str()]);
+        }
+
+        let value_hint = notification.value.map(|value| format!("int:value:{value}"));
+        if let Some(value_hint) = &value_hint {
+            args.extend(["-h", value_hint.as_str()]);
+        }
+
+        args.push(notification.summary.as_str());
+        if !notification.body.is_empty() {
+            args.push(notification.body.as_str());
+        }
+
+        command::run("dunstify", &args)?;
+
+        Ok(())
+    }
+}
+
+// The connection which notifications are sent on, opening it if there isn't one yet
+async fn get_connection() -> Result<zbus::Connection, DaemonError> {
+    let existing = CONNECTION.lock().map_err(|_| DaemonError::MutexLockError)?.clone();
+    if let Some(connection) = existing {
+        return Ok(connection);
+    }
+
+    let connection = zbus::Connection::session().await?;
+
+    // A notification may have opened a connection in the meantime, which is kept so both use the same one
+    Ok(CONNECTION
+        .lock()
+        .map_err(|_| DaemonError::MutexLockError)?
+        .get_or_insert(connection)
+        .clone())
+}
+
+/// Runs the action which is chosen on a notification, following the signals of the notification server
+///
+/// # Errors
+/// Returns an error if the session bus cannot be connected to
+/// Returns an error if the signals cannot be subscribed to
+pub async fn monitor_actions(clients_tx: mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+    let connection = get_connection().await?;
+
+    follow_actions(action_signals(&connection).await?, &clients_tx).await
+}
+
+// Signals of the notification server which are sent to the connection
+async fn action_signals(connection: &zbus::Connection) -> Result<MessageStream, DaemonError> {
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface(DBUS_INTERFACE)?
+        .path(DBUS_PATH)?
+        .build();
+
+    Ok(MessageStream::for_match_rule(rule, connection, None).await?)
+}
+
+async fn follow_actions(
+    mut stream: MessageStream,
+    clients_tx: &mpsc::UnboundedSender<ClientMessage>,
+) -> Result<(), DaemonError> {
+    while let Some(message) = stream.next().await {
+        let message = message?;
+        let header = message.header();
+
+        match header.member().map(MemberName::as_str) {
+            Some("ActionInvoked") => {
+                let (id, key): (u32, String) = message.body().deserialize()?;
+
+                let action = ACTIONS
+                    .lock()
+                    .map_err(|_| DaemonError::MutexLockError)?
+                    .get(&id)
+                    .and_then(|actions| actions.get(key.parse::<usize>().ok()?).cloned());
+
+                // Actions may block, so they are ran away from the async tasks
+                if let Some(action) = action {
+                    let clients_tx = clients_tx.clone();
+                    tokio::task::spawn_blocking(move || {
+                        if let Err(e) = action.run(&clients_tx) {
+                            eprintln!("Notification action '{}' failed:\n\t{e}", action.label);
+                        }
+                    });
+                }
+            }
+            Some("NotificationClosed") => {
+                let (id, _reason): (u32, u32) = message.body().deserialize()?;
+
+                ACTIONS.lock().map_err(|_| DaemonError::MutexLockError)?.remove(&id);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(urgency: Urgency) -> Notification {
+        let channel = NotificationChannel {
+            urgency,
+            ..NotificationChannel::default()
+        };
+
+        Notification::new(&channel, "Battery", "battery-caution", &[])
+    }
+
+    #[test]
+    fn dnd_hides_routine_notifications() {
+        assert!(notification(Urgency::Low).hidden_by_dnd(true));
+        assert!(notification(Urgency::Normal).hidden_by_dnd(true));
+        assert!(!notification(Urgency::Normal).hidden_by_dnd(false));
+    }
+
+    #[test]
+    fn dnd_lets_critical_notifications_through() {
+        assert!(!notification(Urgency::Critical).hidden_by_dnd(true));
+    }
+
+    // What the fake notification server was given by ``Notify``
+    #[derive(Debug, Clone)]
+    struct Received {
+        app_name: String,
+        replaces_id: u32,
+        icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        urgency: Option<u8>,
+        timeout: i32,
+    }
+
+    // Ids are global, so each test's server hands out its own range of them
+    struct FakeServer {
+        first_id: u32,
+        received: std::sync::Arc<Mutex<Vec<Received>>>,
+    }
+
+    impl FakeServer {
+        fn new(first_id: u32) -> Self {
+            Self {
+                first_id,
+                received: std::sync::Arc::default(),
+            }
+        }
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl FakeServer {
+        #[allow(clippy::too_many_arguments, clippy::needless_pass_by_value)]
+        fn notify(
+            &self,
+            app_name: String,
+            replaces_id: u32,
+            icon: String,
+            summary: String,
+            body: String,
+            actions: Vec<String>,
+            hints: HashMap<String, zbus::zvariant::OwnedValue>,
+            timeout: i32,
+        ) -> zbus::fdo::Result<u32> {
+            let mut received = self
+                .received
+                .lock()
+                .map_err(|_| zbus::fdo::Error::Failed("poisoned".to_string()))?;
+
+            received.push(Received {
+                app_name,
+                replaces_id,
+                icon,
+                summary,
+                body,
+                actions,
+                urgency: hints.get("urgency").and_then(|urgency| u8::try_from(urgency).ok()),
+                timeout,
+            });
+
+            // Replaced notifications keep their id, new ones get the next free one
+            Ok(if replaces_id == 0 {
+                u32::try_from(received.len()).map_err(|e| zbus::fdo::Error::Failed(e.to_string()))? + self.first_id
+            } else {
+                replaces_id
+            })
+        }
+    }
+
+    // A server and a client connected to each other without a bus in between
+    async fn peer_connections(server: FakeServer) -> Result<(zbus::Connection, zbus::Connection), DaemonError> {
+        let (server_stream, client_stream) = std::os::unix::net::UnixStream::pair()?;
+
+        let server = zbus::connection::Builder::async_io_unix_stream(server_stream)
+            .server(zbus::Guid::generate())?
+            .p2p()
+            .serve_at(DBUS_PATH, server)?
+            .build();
+        let client = zbus::connection::Builder::async_io_unix_stream(client_stream)
+            .p2p()
+            .build();
+
+        Ok(futures_util::try_join!(server, client)?)
+    }
+
+    async fn send_on(client: &zbus::Connection, notification: Notification) -> Result<(), DaemonError> {
+        let client = Connection::from(client.clone());
+
+        // The blocking connection can't be used from within the runtime
+        tokio::task::spawn_blocking(move || Notifier::send_dbus_on(&client, &notification)).await?
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sends_and_replaces_notifications_over_dbus() -> Result<(), DaemonError> {
+        let server = FakeServer::new(1000);
+        let received = server.received.clone();
+        let (_server, client) = peer_connections(server).await?;
+
+        let channel = NotificationChannel {
+            replace_id: 91_001,
+            urgency: Urgency::Low,
+            actions: vec![NotificationAction::set("Turn off", "bluetooth state false")],
+            ..NotificationChannel::default()
+        };
+
+        send_on(&client, Notification::new(&channel, "Bluetooth on", "bluetooth", &[])).await?;
+        send_on(&client, Notification::new(&channel, "Bluetooth off", "bluetooth", &[])).await?;
+
+        let received = received.lock().map_err(|_| DaemonError::MutexLockError)?.clone();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].app_name, APP_NAME);
+        assert_eq!(received[0].replaces_id, 0);
+        assert_eq!(received[0].icon, "bluetooth");
+        assert_eq!(received[0].summary, "Bluetooth on");
+        assert_eq!(received[0].body, "");
+        assert_eq!(received[0].actions, ["0", "Turn off"]);
+        assert_eq!(received[0].urgency, Some(Urgency::Low as u8));
+        assert_eq!(received[0].timeout, i32::try_from(NOTIFICATION_TIMEOUT)?);
+
+        // The second notification replaces the first, using the id the server gave it
+        assert_eq!(received[1].summary, "Bluetooth off");
+        assert_eq!(received[1].replaces_id, 1001);
+        assert_eq!(
+            SERVER_IDS.lock().map_err(|_| DaemonError::MutexLockError)?.get(&91_001),
+            Some(&1001)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn runs_chosen_actions_until_closed() -> Result<(), DaemonError> {
+        let marker = std::env::temp_dir().join(format!("bar_daemon_action_{}", std::process::id()));
+        let _ = std::fs::remove_file(&marker);
+
+        let (server, client) = peer_connections(FakeServer::new(2000)).await?;
+
+        // Critical notifications aren't replaced, so the server gives this one a new id
+        let channel = NotificationChannel {
+            urgency: Urgency::Critical,
+            actions: vec![NotificationAction::command(
+                "Mark",
+                &format!("touch '{}'", marker.display()),
+            )],
+            ..NotificationChannel::default()
+        };
+        send_on(
+            &client,
+            Notification::new(&channel, "Battery low", "battery-caution", &[]),
+        )
+        .await?;
+        assert!(ACTIONS
+            .lock()
+            .map_err(|_| DaemonError::MutexLockError)?
+            .contains_key(&2001));
+
+        let (clients_tx, _clients_rx) = mpsc::unbounded_channel();
+        let signals = action_signals(&client).await?;
+        let actions = tokio::spawn(async move { follow_actions(signals, &clients_tx).await });
+
+        server
+            .emit_signal(
+                None::<&str>,
+                DBUS_PATH,
+                DBUS_INTERFACE,
+                "ActionInvoked",
+                &(2001_u32, "0"),
+            )
+            .await?;
+
+        for _ in 0..100 {
+            if marker.exists() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(marker.exists());
+        std::fs::remove_file(&marker)?;
+
+        server
+            .emit_signal(
+                None::<&str>,
+                DBUS_PATH,
+                DBUS_INTERFACE,
+                "NotificationClosed",
+                &(2001_u32, 2_u32),
+            )
+            .await?;
+
+        for _ in 0..100 {
+            if !ACTIONS
+                .lock()
+                .map_err(|_| DaemonError::MutexLockError)?
+                .contains_key(&2001)
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!ACTIONS
+            .lock()
+            .map_err(|_| DaemonError::MutexLockError)?
+            .contains_key(&2001));
+
+        actions.abort();
+
+        Ok(())
+    }
+}
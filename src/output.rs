@@ -0,0 +1,185 @@
+use serde_json::{json, Value};
+
+use crate::{
+    daemon::DaemonReply,
+    error::DaemonError,
+    i3bar,
+    json::{self, tuples_to_json, tuples_to_object},
+    notification::render,
+    tuples::{AllTuples, TupleValue, Tuples},
+    waybar,
+};
+
+/// How replies and listener lines are printed
+#[derive(Debug, Clone, Default)]
+pub enum OutputMode {
+    /// The reply as it is sent by the daemon
+    #[default]
+    Debug,
+    /// Only the value, or the JSON of the tuples
+    Raw,
+    /// A template with ``{module.key}`` placeholders, or ``{key}`` and ``{value}`` for a single module or value
+    ///
+    /// Set replies also have ``{changed}`` and ``{clamped}``
+    Format(String),
+    /// The JSON of a Waybar custom module, for the named module
+    Waybar(String),
+    /// Blocks of the i3bar protocol, for every module
+    I3bar,
+}
+
+impl OutputMode {
+    #[must_use]
+    pub fn new(format: Option<String>, raw: bool, waybar: Option<String>, i3bar: bool) -> Self {
+        match (format, waybar) {
+            _ if i3bar => Self::I3bar,
+            (_, Some(module)) => Self::Waybar(module),
+            (Some(format), None) => Self::Format(format),
+            (None, None) if raw => Self::Raw,
+            (None, None) => Self::Debug,
+        }
+    }
+
+    /// # Errors
+    /// Returns an error if the tuples cannot be converted into JSON
+    /// Returns an error if the Waybar module is not known
+    pub fn reply_to_string(&self, reply: &DaemonReply) -> Result<String, DaemonError> {
+        Ok(match self {
+            Self::Debug => match reply {
+                DaemonReply::Error { kind, message } => format!("Error ({kind}): {message}"),
+                reply => format!("{reply:?}"),
+            },
+            Self::Raw => match reply {
+                DaemonReply::Value { value, .. } => value.clone(),
+                DaemonReply::Tuples { tuples, .. } => serde_json::to_string(&tuples_to_object(tuples))?,
+                DaemonReply::AllTuples { tuples } => tuples_to_json(tuples.clone())?,
+                DaemonReply::Set {
+                    tuples,
+                    changed,
+                    clamped,
+                    ..
+                } => json!({ "values": tuples_to_object(tuples), "changed": changed, "clamped": clamped }).to_string(),
+                DaemonReply::Error { message, .. } => message.clone(),
+            },
+            Self::Format(template) => match reply {
+                DaemonReply::Value { value, .. } => render(template, &[("value", value)]),
+                DaemonReply::Tuples { tuples, .. } => render(template, &placeholders(tuples)),
+                DaemonReply::AllTuples { tuples } => render(template, &placeholders(&flatten(tuples))),
+                DaemonReply::Set {
+                    tuples,
+                    changed,
+                    clamped,
+                    ..
+                } => render(
+                    template,
+                    &[
+                        placeholders(tuples),
+                        vec![("changed", changed.to_string()), ("clamped", clamped.to_string())],
+                    ]
+                    .concat(),
+                ),
+                DaemonReply::Error { kind, message } => format!("Error ({kind}): {message}"),
+            },
+            Self::Waybar(module) => {
+                let waybar_module = waybar::get_module(module)?;
+
+                match reply {
+                    // A single value has no tuples for the templates, so it is shown as it is
+                    DaemonReply::Value { value, .. } => json!({ "text": value }),
+                    DaemonReply::Tuples { tuples, .. } | DaemonReply::Set { tuples, .. } => {
+                        waybar_module.to_json(tuples)
+                    }
+                    DaemonReply::AllTuples { tuples } => waybar_module.to_json(find_module(tuples, module)),
+                    DaemonReply::Error { kind, message } => {
+                        json!({ "text": message, "tooltip": message, "class": ["error", kind.to_string()] })
+                    }
+                }
+                .to_string()
+            }
+            Self::I3bar => match reply {
+                DaemonReply::AllTuples { tuples } => i3bar::to_blocks(tuples),
+                DaemonReply::Error { kind, message } => format!("Error ({kind}): {message}"),
+                // Blocks need the name of every module
+                _ => format!("{reply:?}"),
+            },
+        })
+    }
+
+    /// Prints a line which the listener received, which is the JSON of every module's tuples
+    ///
+    /// # Errors
+    /// Returns an error if the line is not valid JSON
+    /// Returns an error if the Waybar module is not known
+    pub fn line_to_string(&self, line: &str) -> Result<String, DaemonError> {
+        Ok(match self {
+            // The line was written with the daemon's config, so it is written again for ``--string-values``
+            Self::Debug | Self::Raw if json::string_values() => tuples_to_json(parse_line(line)?)?,
+            Self::Debug | Self::Raw => line.to_string(),
+            Self::Format(template) => render(template, &placeholders(&flatten(&parse_line(line)?))),
+            Self::Waybar(module) => waybar::get_module(module)?
+                .to_json(find_module(&parse_line(line)?, module))
+                .to_string(),
+            Self::I3bar => i3bar::to_blocks(&parse_line(line)?),
+        })
+    }
+}
+
+// The tuples of every module, from the JSON which the listener receives
+fn parse_line(line: &str) -> Result<AllTuples, DaemonError> {
+    let json: Value = serde_json::from_str(line)?;
+
+    Ok(json
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, tuples)| {
+            (
+                name.clone(),
+                tuples
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, value)| (key.clone(), TupleValue::from(value)))
+                    .collect(),
+            )
+        })
+        .collect())
+}
+
+// Every module's tuples as ``module.key``
+fn flatten(tuples: &[(String, Tuples)]) -> Tuples {
+    tuples
+        .iter()
+        .flat_map(|(name, tuples)| {
+            tuples
+                .iter()
+                .map(move |(key, value)| (format!("{name}.{key}"), value.clone()))
+        })
+        .collect()
+}
+
+/// The value of a tuple as a whole percentage, for thresholds
+#[must_use]
+pub fn get_percentage(tuples: &[(String, TupleValue)], key: &str) -> Option<u32> {
+    tuples
+        .iter()
+        .find(|(name, _)| name == key)
+        .and_then(|(_, value)| value.as_f64())
+        .map(|value| value.round().max(0.) as u32)
+}
+
+fn find_module<'a>(tuples: &'a [(String, Tuples)], module: &str) -> &'a [(String, TupleValue)] {
+    tuples
+        .iter()
+        .find(|(name, _)| name == module)
+        .map_or(&[], |(_, tuples)| tuples.as_slice())
+}
+
+/// The tuples as placeholders for templates, where unavailable values are empty
+#[must_use]
+pub fn placeholders(tuples: &[(String, TupleValue)]) -> Vec<(&str, String)> {
+    tuples
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.to_string()))
+        .collect()
+}
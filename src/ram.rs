@@ -6,9 +6,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     command,
+    config::CONFIG,
     daemon::{DaemonItem, DaemonMessage, DaemonReply},
     error::DaemonError,
-    ICON_END, ICON_EXT,
+    icon::{self, IconStyle},
+    tuples::Tuples,
 };
 
 #[derive(Subcommand)]
@@ -20,7 +22,10 @@ pub enum RamGetCommands {
     #[command(alias = "per", alias = "p")]
     Percent,
     #[command(alias = "i")]
-    Icon,
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -28,7 +33,7 @@ pub enum RamItem {
     Total,
     Used,
     Percent,
-    Icon,
+    Icon(Option<IconStyle>),
     All,
 }
 
@@ -115,22 +120,26 @@ impl Ram {
     }
 
     #[must_use]
-    pub fn get_icon() -> String {
-        format!("nvidia-ram{ICON_END}")
+    pub fn get_icon(style: IconStyle, percent: u64) -> String {
+        CONFIG.icons.maps(style).ram.get(None, percent as u32)
     }
 
     /// # Errors
     /// Returns an error if the requested value could not be parsed
-    pub fn get_tuples() -> Result<Vec<(String, String)>, DaemonError> {
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
         let (total, used, percent) = Self::get()?;
-        let icon = Self::get_icon();
-
-        Ok(vec![
-            ("total".to_string(), total.to_string()),
-            ("used".to_string(), used.to_string()),
-            ("percent".to_string(), percent.to_string()),
-            ("icon".to_string(), format!("{icon}{ICON_EXT}")),
-        ])
+        let icon = Self::get_icon(IconStyle::Name, percent);
+        let glyph = Self::get_icon(IconStyle::Glyph, percent);
+
+        Ok([
+            vec![
+                ("total".to_string(), total.into()),
+                ("used".to_string(), used.into()),
+                ("percent".to_string(), percent.into()),
+            ],
+            icon::to_tuples("", &icon, &glyph),
+        ]
+        .concat())
     }
 
     /// # Errors
@@ -151,9 +160,9 @@ impl Ram {
                     item,
                     value: Self::get_percent()?.to_string(),
                 },
-                RamItem::Icon => DaemonReply::Value {
+                RamItem::Icon(style) => DaemonReply::Value {
                     item,
-                    value: Self::get_icon(),
+                    value: Self::get_icon(style.unwrap_or(CONFIG.icons.style), Self::get_percent()?),
                 },
                 RamItem::All => DaemonReply::Tuples {
                     item,
@@ -171,7 +180,7 @@ impl Ram {
                     RamGetCommands::Total => DaemonItem::Ram(RamItem::Total),
                     RamGetCommands::Used => DaemonItem::Ram(RamItem::Used),
                     RamGetCommands::Percent => DaemonItem::Ram(RamItem::Percent),
-                    RamGetCommands::Icon => DaemonItem::Ram(RamItem::Icon),
+                    RamGetCommands::Icon { style } => DaemonItem::Ram(RamItem::Icon(*style)),
                 },
                 None => DaemonItem::Ram(RamItem::All),
             },
@@ -0,0 +1,285 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, Utc};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+use crate::{
+    brightness::BrightnessItem, cli::parse_duration, config::CONFIG, daemon::DaemonItem, error::DaemonError, fade,
+    listener::ClientMessage,
+};
+
+// How often the schedule is checked for a new slot
+const SCHEDULE_CHECK_RATE: u64 = 30_000;
+
+// Julian date of the unix epoch, and of the J2000 epoch
+const JULIAN_UNIX_EPOCH: f64 = 2_440_587.5;
+const JULIAN_2000: f64 = 2_451_545.0;
+
+// Set when the user changes the monitor brightness, cleared at the start of the next slot
+static MANUAL_OVERRIDE: AtomicBool = AtomicBool::new(false);
+
+/// The time at which a slot starts, either a time of day or an offset from sunrise/sunset
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(try_from = "String")]
+pub enum SlotTime {
+    Fixed(NaiveTime),
+    Sunrise(TimeDelta),
+    Sunset(TimeDelta),
+}
+
+impl TryFrom<String> for SlotTime {
+    type Error = String;
+
+    /// Parses ``07:30``, ``sunrise``, ``sunset+30m`` or ``sunrise-1h``
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let value = value.trim().to_lowercase();
+
+        for (name, slot_time) in [
+            ("sunrise", Self::Sunrise as fn(TimeDelta) -> Self),
+            ("sunset", Self::Sunset),
+        ] {
+            let Some(offset) = value.strip_prefix(name) else {
+                continue;
+            };
+
+            let offset = offset.trim();
+            if offset.is_empty() {
+                return Ok(slot_time(TimeDelta::zero()));
+            }
+
+            // Split the sign from the duration
+            let (sign, duration) = offset.split_at(1);
+            let duration =
+                TimeDelta::milliseconds(i64::try_from(parse_duration(duration)?).map_err(|e| e.to_string())?);
+
+            return match sign {
+                "+" => Ok(slot_time(duration)),
+                "-" => Ok(slot_time(-duration)),
+                _ => Err(format!(
+                    "Invalid offset '{offset}' for {name}. Use +<duration> or -<duration>."
+                )),
+            };
+        }
+
+        NaiveTime::parse_from_str(&value, "%H:%M")
+            .map(Self::Fixed)
+            .map_err(|e| format!("Invalid time '{value}'. Use HH:MM, sunrise or sunset:\n\t{e}"))
+    }
+}
+
+impl SlotTime {
+    /// Finds when this slot starts on the given date, which is ``None`` when the sun doesn't rise or set
+    ///
+    /// # Errors
+    /// Returns an error if sunrise or sunset is used without coordinates in the config
+    pub fn resolve(self, date: NaiveDate) -> Result<Option<DateTime<Local>>, DaemonError> {
+        let (sun_times, offset) = match self {
+            Self::Fixed(time) => return Ok(date.and_time(time).and_local_timezone(Local).earliest()),
+            Self::Sunrise(offset) | Self::Sunset(offset) => {
+                let (Some(latitude), Some(longitude)) = (CONFIG.schedule.latitude, CONFIG.schedule.longitude) else {
+                    return Err(DaemonError::ScheduleError(
+                        "Sunrise and sunset need a latitude and longitude".to_string(),
+                    ));
+                };
+
+                (get_sun_times(date, latitude, longitude), offset)
+            }
+        };
+
+        Ok(sun_times.map(|(sunrise, sunset)| {
+            let time = if matches!(self, Self::Sunrise(_)) {
+                sunrise
+            } else {
+                sunset
+            };
+
+            time.with_timezone(&Local) + offset
+        }))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct ScheduleSlot {
+    pub time: SlotTime,
+    pub percent: u32,
+}
+
+/// Pauses the schedule until the next slot starts
+pub fn set_manual_override() {
+    MANUAL_OVERRIDE.store(true, Ordering::Relaxed);
+}
+
+/// Calculates sunrise and sunset using the sunrise equation, ``None`` during polar day or night
+#[must_use]
+#[allow(clippy::suboptimal_flops)]
+pub fn get_sun_times(date: NaiveDate, latitude: f64, longitude: f64) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    // Days since J2000, at the start of the date
+    let julian_date = f64::from(date.num_days_from_ce() - 719_163) + JULIAN_UNIX_EPOCH;
+    let day = (julian_date - JULIAN_2000 + 0.0008).ceil();
+
+    // Mean solar time, solar mean anomaly and equation of the center
+    let mean_solar_time = day - longitude / 360.0;
+    let mean_anomaly = (357.5291 + 0.985_600_28 * mean_solar_time)
+        .rem_euclid(360.0)
+        .to_radians();
+    let center =
+        1.9148 * mean_anomaly.sin() + 0.0200 * (2.0 * mean_anomaly).sin() + 0.0003 * (3.0 * mean_anomaly).sin();
+
+    // Ecliptic longitude, and the time of solar noon
+    let ecliptic_longitude = (mean_anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let solar_transit =
+        JULIAN_2000 + mean_solar_time + 0.0053 * mean_anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    // Declination of the sun, then the hour angle when the sun crosses the horizon
+    let declination = (ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+    let latitude = latitude.to_radians();
+    let cos_hour_angle =
+        ((-0.833_f64).to_radians().sin() - latitude.sin() * declination.sin()) / (latitude.cos() * declination.cos());
+
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    let to_utc = |julian_date: f64| {
+        DateTime::from_timestamp_millis(((julian_date - JULIAN_UNIX_EPOCH) * 86_400_000.0).round() as i64)
+    };
+
+    Some((
+        to_utc(solar_transit - hour_angle / 360.0)?,
+        to_utc(solar_transit + hour_angle / 360.0)?,
+    ))
+}
+
+/// Finds the slot which is active at ``now``, along with when it started
+fn get_active_slot(now: DateTime<Local>) -> Result<Option<(DateTime<Local>, u32)>, DaemonError> {
+    let today = now.date_naive();
+
+    // Yesterday's slots are needed for the time before today's first slot
+    let mut slots = Vec::new();
+    for date in [today.pred_opt(), Some(today)].into_iter().flatten() {
+        for slot in &CONFIG.schedule.slots {
+            if let Some(start) = slot.time.resolve(date)? {
+                slots.push((start, slot.percent));
+            }
+        }
+    }
+
+    Ok(slots
+        .into_iter()
+        .filter(|(start, _)| *start <= now)
+        .max_by_key(|(start, _)| *start))
+}
+
+fn apply(percent: u32, clients_tx: &mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+    fade::start(
+        DaemonItem::Brightness(BrightnessItem::Monitor),
+        percent.to_string().as_str(),
+        CONFIG.schedule.fade,
+        clients_tx.clone(),
+    )?;
+
+    Ok(())
+}
+
+/// Applies the brightness of each slot as it starts
+///
+/// # Errors
+/// Returns an error if a slot's time cannot be resolved
+/// Returns an error if the brightness cannot be set
+pub async fn run(clients_tx: mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+    let mut applied_slot = None;
+    let mut last_check = Local::now();
+
+    loop {
+        let now = Local::now();
+
+        // A long gap between checks means that the system has been suspended
+        let resumed = now - last_check > TimeDelta::milliseconds(i64::try_from(SCHEDULE_CHECK_RATE * 2)?);
+        last_check = now;
+
+        if let Some((start, percent)) = get_active_slot(now)? {
+            if applied_slot != Some(start) {
+                // A new slot ends any manual override
+                MANUAL_OVERRIDE.store(false, Ordering::Relaxed);
+
+                apply(percent, &clients_tx)?;
+                applied_slot = Some(start);
+            } else if resumed && !MANUAL_OVERRIDE.load(Ordering::Relaxed) {
+                // The firmware may have reset the brightness while suspended
+                apply(percent, &clients_tx)?;
+            }
+        }
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(SCHEDULE_CHECK_RATE)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Minutes between the calculated time and the expected time
+    fn minutes_from(time: DateTime<Utc>, expected: &str) -> i64 {
+        DateTime::parse_from_rfc3339(expected).map_or(i64::MAX, |expected| {
+            (time - expected.with_timezone(&Utc)).num_minutes().abs()
+        })
+    }
+
+    #[test]
+    fn sun_times_in_london() {
+        let times = NaiveDate::from_ymd_opt(2024, 6, 21).and_then(|date| get_sun_times(date, 51.5, -0.12));
+        let Some((sunrise, sunset)) = times else {
+            panic!("The sun should rise and set in London");
+        };
+
+        assert!(minutes_from(sunrise, "2024-06-21T03:43:00Z") <= 3, "{sunrise}");
+        assert!(minutes_from(sunset, "2024-06-21T20:21:00Z") <= 3, "{sunset}");
+    }
+
+    #[test]
+    fn sun_times_in_sydney() {
+        let times = NaiveDate::from_ymd_opt(2024, 12, 21).and_then(|date| get_sun_times(date, -33.87, 151.21));
+        let Some((sunrise, sunset)) = times else {
+            panic!("The sun should rise and set in Sydney");
+        };
+
+        assert!(minutes_from(sunrise, "2024-12-20T18:41:00Z") <= 3, "{sunrise}");
+        assert!(minutes_from(sunset, "2024-12-21T09:05:00Z") <= 3, "{sunset}");
+    }
+
+    #[test]
+    fn no_sun_times_in_polar_day_or_night() {
+        for (month, day) in [(6, 21), (12, 21)] {
+            let date = NaiveDate::from_ymd_opt(2024, month, day);
+
+            assert!(date.and_then(|date| get_sun_times(date, 78.22, 15.65)).is_none());
+        }
+    }
+
+    #[test]
+    fn parses_slot_times() {
+        assert!(matches!(
+            SlotTime::try_from("07:30".to_string()),
+            Ok(SlotTime::Fixed(_))
+        ));
+        assert!(matches!(
+            SlotTime::try_from("sunrise".to_string()),
+            Ok(SlotTime::Sunrise(offset)) if offset.is_zero()
+        ));
+        assert!(matches!(
+            SlotTime::try_from("sunset+30m".to_string()),
+            Ok(SlotTime::Sunset(offset)) if offset == TimeDelta::minutes(30)
+        ));
+        assert!(matches!(
+            SlotTime::try_from("Sunrise-1h".to_string()),
+            Ok(SlotTime::Sunrise(offset)) if offset == TimeDelta::hours(-1)
+        ));
+        assert!(SlotTime::try_from("sunset*2".to_string()).is_err());
+        assert!(SlotTime::try_from("25:00".to_string()).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bluetooth::Bluetooth,
+    brightness::{Brightness, BrightnessItem},
+    config::{CONFIG, CONFIG_DIR_NAME},
+    error::DaemonError,
+    fan_profile::{FanProfile, FAN_STATE_STRINGS},
+    listener::ClientMessage,
+    volume::Volume,
+};
+
+pub const STATE_FILE_NAME: &str = "state.json";
+
+// How often the updates which have piled up are saved, so a fade writes the state once rather than on every step
+pub const SAVE_RATE: u64 = 1000;
+
+// The state which was last written, so the file is only written when something changes
+static STATE: LazyLock<Mutex<State>> = LazyLock::new(|| {
+    Mutex::new(State::load().unwrap_or_else(|e| {
+        eprintln!("Could not load state:\n\t{e}");
+
+        State::default()
+    }))
+});
+
+// Updates which haven't been saved yet
+static PENDING: LazyLock<Mutex<Vec<ClientMessage>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Values which are remembered across restarts, ``None`` when they have never been read
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct State {
+    pub volume_percent: Option<u32>,
+    pub volume_mute: Option<bool>,
+    pub monitor_percent: Option<u32>,
+    pub keyboard_percent: Option<u32>,
+    pub bluetooth: Option<bool>,
+    pub fan_profile: Option<String>,
+}
+
+impl State {
+    /// # Errors
+    /// Returns an error if the state file exists but cannot be read
+    /// Returns an error if the state file is not valid JSON
+    pub fn load() -> Result<Self, DaemonError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// # Errors
+    /// Returns an error if the state directory cannot be created
+    /// Returns an error if the state file cannot be written
+    pub fn save(&self) -> Result<(), DaemonError> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        // Write to a temporary file first, so a crash can't leave a partial state file
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(temp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Location of the state file, following the XDG base directory spec
+    #[must_use]
+    pub fn path() -> Option<PathBuf> {
+        let state_home = std::env::var_os("XDG_STATE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))?;
+
+        Some(state_home.join(CONFIG_DIR_NAME).join(STATE_FILE_NAME))
+    }
+
+    /// Remembers that a value has been updated, it is read and saved by the next ``State::flush``
+    ///
+    /// # Errors
+    /// Returns an error if the pending mutex cannot be locked
+    pub fn queue_update(client_message: ClientMessage) -> Result<(), DaemonError> {
+        let mut pending = PENDING.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+        if !pending.contains(&client_message) {
+            pending.push(client_message);
+        }
+
+        drop(pending);
+
+        Ok(())
+    }
+
+    /// Reads and saves the queued updates, away from the async tasks since the commands block
+    ///
+    /// # Errors
+    /// Returns an error if the pending mutex cannot be locked
+    /// Returns an error if the blocking task could not be joined
+    /// Returns an error if the state file cannot be written
+    pub async fn flush() -> Result<(), DaemonError> {
+        let pending = std::mem::take(&mut *PENDING.lock().map_err(|_| DaemonError::MutexLockError)?);
+
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        tokio::task::spawn_blocking(move || Self::update(&pending)).await?
+    }
+
+    /// Reads the values which have been updated, writing the state file if any of them changed
+    ///
+    /// # Errors
+    /// Returns an error if the state mutex cannot be locked
+    /// Returns an error if the state file cannot be written
+    fn update(client_messages: &[ClientMessage]) -> Result<(), DaemonError> {
+        let mut state = STATE.lock().map_err(|_| DaemonError::MutexLockError)?;
+        let mut new_state = state.clone();
+
+        // Values which can't be read are left as they were
+        let updated = |client_message: ClientMessage| {
+            client_messages.contains(&ClientMessage::UpdateAll) || client_messages.contains(&client_message)
+        };
+
+        if updated(ClientMessage::UpdateVolume) {
+            new_state.volume_percent = Volume::get_percent().ok().or(new_state.volume_percent);
+            new_state.volume_mute = Volume::get_mute().ok().or(new_state.volume_mute);
+        }
+
+        if updated(ClientMessage::UpdateBrightness) {
+            new_state.monitor_percent = Brightness::get_monitor().ok().or(new_state.monitor_percent);
+            new_state.keyboard_percent = Brightness::get_keyboard().ok().or(new_state.keyboard_percent);
+        }
+
+        if updated(ClientMessage::UpdateBluetooth) {
+            new_state.bluetooth = Bluetooth::get_state().ok().or(new_state.bluetooth);
+        }
+
+        if updated(ClientMessage::UpdateFanProfile) {
+            new_state.fan_profile = FanProfile::get_profile()
+                .ok()
+                .map(|profile| FAN_STATE_STRINGS[profile as usize].to_string())
+                .or_else(|| new_state.fan_profile.clone());
+        }
+
+        if new_state != *state {
+            new_state.save()?;
+
+            *state = new_state;
+        }
+
+        drop(state);
+
+        Ok(())
+    }
+
+    /// Sets the values which are enabled in the ``[restore]`` config from the state file
+    ///
+    /// # Errors
+    /// Returns an error if the state mutex cannot be locked
+    pub fn restore() -> Result<(), DaemonError> {
+        let state = STATE.lock().map_err(|_| DaemonError::MutexLockError)?.clone();
+
+        let mut results = Vec::new();
+
+        if CONFIG.restore.volume {
+            // The exact linear percentage is restored, rather than one derived from the device
+            if let Some(percent) = state.volume_percent {
+                results.push(("volume", Volume::set_boost(percent.to_string().as_str())));
+            }
+
+            if let Some(mute) = state.volume_mute {
+                results.push(("volume mute", Volume::set_mute(mute.to_string().as_str())));
+            }
+        }
+
+        if CONFIG.restore.brightness {
+            if let Some(percent) = state.monitor_percent {
+                results.push((
+                    "monitor brightness",
+                    Brightness::set_item_quiet(&BrightnessItem::Monitor, percent.to_string().as_str()),
+                ));
+            }
+        }
+
+        if CONFIG.restore.keyboard {
+            if let Some(percent) = state.keyboard_percent {
+                results.push((
+                    "keyboard brightness",
+                    Brightness::set_item_quiet(&BrightnessItem::Keyboard, percent.to_string().as_str()),
+                ));
+            }
+        }
+
+        if CONFIG.restore.bluetooth {
+            if let Some(bluetooth) = state.bluetooth {
+                results.push(("bluetooth", Bluetooth::set_state(bluetooth.to_string().as_str())));
+            }
+        }
+
+        if CONFIG.restore.fan_profile {
+            if let Some(profile) = state.fan_profile {
+                results.push(("fan profile", FanProfile::set_profile(&profile)));
+            }
+        }
+
+        // A device which is missing shouldn't stop the others from being restored
+        for (name, result) in results {
+            if let Err(e) = result {
+                eprintln!("Could not restore the {name}:\n\t{e}");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,128 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
 use crate::{
-    battery::Battery, bluetooth::Bluetooth, brightness::Brightness, error::DaemonError, fan_profile::FanProfile, ram::Ram,
-    volume::Volume,
+    battery::Battery, bluetooth::Bluetooth, brightness::Brightness, dnd::Dnd, error::DaemonError, fan_profile::FanProfile,
+    ram::Ram, volume::Volume,
 };
 
-pub const TUPLE_NAMES: &[&str] = &["volume", "brightness", "bluetooth", "battery", "ram", "fan_profile"];
+/// A module's values, each paired with its key
+pub type Tuples = Vec<(String, TupleValue)>;
+
+/// Each module's name, paired with its tuples
+pub type AllTuples = Vec<(String, Tuples)>;
+
+/// A value in the tuples, which keeps its type in the JSON output
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum TupleValue {
+    /// The value is unavailable, such as the battery time while charging is finished
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl TupleValue {
+    /// The value in JSON, or the value as a string when ``string_values`` is set, as the JSON output used to be
+    #[must_use]
+    pub fn to_json(&self, string_values: bool) -> Value {
+        if string_values {
+            return Value::String(self.to_string());
+        }
+
+        match self {
+            Self::Null => Value::Null,
+            Self::Bool(value) => Value::Bool(*value),
+            Self::Int(value) => Value::from(*value),
+            Self::Float(value) => Value::from(*value),
+            Self::String(value) => Value::String(value.clone()),
+        }
+    }
+
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(value) => Some(*value as f64),
+            Self::Float(value) => Some(*value),
+            Self::String(value) => value.parse().ok(),
+            Self::Null | Self::Bool(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for TupleValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => Ok(()),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::String(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<bool> for TupleValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<u32> for TupleValue {
+    fn from(value: u32) -> Self {
+        Self::Int(i64::from(value))
+    }
+}
+
+impl From<u64> for TupleValue {
+    fn from(value: u64) -> Self {
+        Self::Int(i64::try_from(value).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<f64> for TupleValue {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for TupleValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for TupleValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for TupleValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+impl From<&Value> for TupleValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Null,
+            Value::Bool(value) => Self::Bool(*value),
+            Value::Number(number) => number
+                .as_i64()
+                .map_or_else(|| Self::Float(number.as_f64().unwrap_or_default()), Self::Int),
+            Value::String(value) => Self::String(value.clone()),
+            // Modules don't have nested values, so these are kept as their JSON
+            Value::Array(_) | Value::Object(_) => Self::String(value.to_string()),
+        }
+    }
+}
+
+pub const TUPLE_NAMES: &[&str] = &["volume", "brightness", "bluetooth", "battery", "ram", "fan_profile", "dnd"];
 
 #[derive(Copy, Clone)]
 pub enum TupleName {
@@ -13,6 +132,7 @@ pub enum TupleName {
     Battery = 3,
     Ram = 4,
     FanProfile = 5,
+    Dnd = 6,
 }
 
 impl TryFrom<usize> for TupleName {
@@ -28,6 +148,7 @@ impl TryFrom<usize> for TupleName {
             3 => Ok(Self::Battery),
             4 => Ok(Self::Ram),
             5 => Ok(Self::FanProfile),
+            6 => Ok(Self::Dnd),
             _ => Err(Self::Error::TupleNameError),
         }
     }
@@ -35,7 +156,7 @@ impl TryFrom<usize> for TupleName {
 
 /// # Errors
 /// Returns an error if the specified tuples can't be gotten
-pub fn tuple_name_to_tuples(tuple_name: &TupleName) -> Result<Vec<(String, String)>, DaemonError> {
+pub fn tuple_name_to_tuples(tuple_name: &TupleName) -> Result<Tuples, DaemonError> {
     match tuple_name {
         TupleName::Volume => Volume::get_tuples(),
         TupleName::Brightness => Brightness::get_tuples(),
@@ -43,12 +164,13 @@ pub fn tuple_name_to_tuples(tuple_name: &TupleName) -> Result<Vec<(String, Strin
         TupleName::Battery => Battery::get_tuples(),
         TupleName::Ram => Ram::get_tuples(),
         TupleName::FanProfile => FanProfile::get_tuples(),
+        TupleName::Dnd => Dnd::get_tuples(),
     }
 }
 
 /// # Errors
 /// Returns an error if the requested value could not be parsed
-pub async fn get_all_tuples() -> Result<Vec<(String, Vec<(String, String)>)>, DaemonError> {
+pub async fn get_all_tuples() -> Result<AllTuples, DaemonError> {
     TUPLE_NAMES
         .iter()
         .enumerate()
@@ -63,3 +185,59 @@ pub async fn get_all_tuples() -> Result<Vec<(String, Vec<(String, String)>)>, Da
         })
         .collect::<Result<Result<Vec<_>, DaemonError>, DaemonError>>()?
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn keeps_types_in_json() {
+        assert_eq!(TupleValue::Null.to_json(false), Value::Null);
+        assert_eq!(TupleValue::Bool(true).to_json(false), json!(true));
+        assert_eq!(TupleValue::Int(-3).to_json(false), json!(-3));
+        assert_eq!(TupleValue::Float(1.5).to_json(false), json!(1.5));
+        assert_eq!(TupleValue::from("on").to_json(false), json!("on"));
+    }
+
+    #[test]
+    fn string_values_in_json() {
+        assert_eq!(TupleValue::Null.to_json(true), json!(""));
+        assert_eq!(TupleValue::Bool(true).to_json(true), json!("true"));
+        assert_eq!(TupleValue::Int(-3).to_json(true), json!("-3"));
+        assert_eq!(TupleValue::Float(1.5).to_json(true), json!("1.5"));
+    }
+
+    #[test]
+    fn from_json() {
+        assert_eq!(TupleValue::from(&json!(null)), TupleValue::Null);
+        assert_eq!(TupleValue::from(&json!(false)), TupleValue::Bool(false));
+        assert_eq!(TupleValue::from(&json!(40)), TupleValue::Int(40));
+        assert_eq!(TupleValue::from(&json!(0.25)), TupleValue::Float(0.25));
+        assert_eq!(
+            TupleValue::from(&json!([1, 2])),
+            TupleValue::String("[1,2]".to_string())
+        );
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        for value in [
+            TupleValue::Null,
+            TupleValue::Bool(true),
+            TupleValue::Int(75),
+            TupleValue::Float(0.5),
+            TupleValue::from("Balanced"),
+        ] {
+            assert_eq!(TupleValue::from(&value.to_json(false)), value);
+        }
+    }
+
+    #[test]
+    fn from_option() {
+        assert_eq!(TupleValue::from(None::<u32>), TupleValue::Null);
+        assert_eq!(TupleValue::from(Some(5_u32)), TupleValue::Int(5));
+        assert_eq!(TupleValue::from(u64::MAX), TupleValue::Int(i64::MAX));
+    }
+}
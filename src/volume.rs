@@ -1,16 +1,26 @@
-use std::sync::{LazyLock, Mutex};
+use std::{
+    process::Stdio,
+    sync::{LazyLock, Mutex},
+};
 
 use crate::{
-    cli::parse_bool,
+    cli::{parse_bool, parse_duration},
     command,
+    config::CONFIG,
     daemon::{DaemonItem, DaemonMessage, DaemonReply},
     error::DaemonError,
-    log_linear::{linear_to_logarithmic, logarithmic_to_linear},
-    ICON_EXT, NOTIFICATION_ID, NOTIFICATION_TIMEOUT,
+    icon::{self, IconStyle},
+    listener::ClientMessage,
+    notification::Notification,
+    tuples::Tuples,
 };
 
 use clap::{ArgAction, Subcommand};
 use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader},
+    sync::mpsc,
+};
 
 #[derive(Subcommand)]
 pub enum VolumeGetCommands {
@@ -19,11 +29,60 @@ pub enum VolumeGetCommands {
     #[command(alias = "m")]
     Mute,
     #[command(alias = "i")]
-    Icon,
+    Icon {
+        #[arg(long, value_enum)]
+        style: Option<IconStyle>,
+    },
+    #[command(alias = "a")]
+    Apps,
+    App {
+        name: String,
+        #[command(subcommand)]
+        commands: Option<VolumeAppGetCommands>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum VolumeAppGetCommands {
+    #[command(alias = "per", alias = "p")]
+    Percent,
+    #[command(alias = "m")]
+    Mute,
 }
 
 #[derive(Subcommand)]
 pub enum VolumeSetCommands {
+    #[command(alias = "per", alias = "p")]
+    Percent {
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+        /// Change the volume gradually over this duration (e.g. 300ms, 2s)
+        #[arg(long, value_parser = parse_duration)]
+        fade: Option<u64>,
+    },
+    #[command(alias = "m")]
+    Mute {
+        #[arg(action = ArgAction::Set, value_parser = parse_bool)]
+        value: Option<bool>,
+    },
+    /// Set the volume past 100%, up to the configured maximum
+    #[command(alias = "b")]
+    Boost {
+        #[arg(allow_hyphen_values = true)]
+        value: String,
+        /// Change the volume gradually over this duration (e.g. 300ms, 2s)
+        #[arg(long, value_parser = parse_duration)]
+        fade: Option<u64>,
+    },
+    App {
+        name: String,
+        #[command(subcommand)]
+        commands: VolumeAppSetCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum VolumeAppSetCommands {
     #[command(alias = "per", alias = "p")]
     Percent {
         #[arg(allow_hyphen_values = true)]
@@ -48,16 +107,46 @@ pub enum VolumeUpdateCommands {
 pub enum VolumeItem {
     Percent,
     Mute,
-    Icon,
+    Boost,
+    Icon(Option<IconStyle>),
+    Apps,
+    App { name: String, item: VolumeAppItem },
     All,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum VolumeAppItem {
+    Percent,
+    Mute,
+    All,
+}
+
+/// An active playback stream, as reported by ``pw-dump``
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AppStream {
+    pub id: u32,
+    pub name: String,
+    pub pid: Option<u32>,
+    pub percent: u32,
+    pub mute: bool,
+}
+
 static VOLUME_PERCENT: LazyLock<Mutex<u32>> = LazyLock::new(|| {
     let percent = Volume::get_percent_true().unwrap_or_else(|e| panic!("Error setting inital VOLUME_PERCENT:\n\t{e}"));
 
     Mutex::new(percent)
 });
 
+static VOLUME_MUTE: LazyLock<Mutex<bool>> = LazyLock::new(|| {
+    let (_, mute) =
+        Volume::get_node_raw("@DEFAULT_SINK@").unwrap_or_else(|e| panic!("Error setting inital VOLUME_MUTE:\n\t{e}"));
+
+    Mutex::new(mute)
+});
+
+// wpctl reports volumes rounded to two decimal places
+const WPCTL_PRECISION: f64 = 0.01;
+
 pub struct Volume;
 
 impl Volume {
@@ -65,16 +154,7 @@ impl Volume {
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
     fn get_percent_true() -> Result<u32, DaemonError> {
-        // Get the volume and mute status as a string
-        let output = command::run("wpctl", &["get-volume", "@DEFAULT_SINK@"])?;
-        let mut output_split = output.trim_start_matches("Volume: ").split_whitespace(); // Left with only volume number, and muted status
-
-        // Take the first part of the split (The numerical part) then convert to linear percentage
-        let percent = if let Some(volume_str) = output_split.next() {
-            logarithmic_to_linear(volume_str.parse::<f64>()? * 100.) as u32
-        } else {
-            return Err(DaemonError::ParseError(output));
-        };
+        let (percent, _) = Self::get_node("@DEFAULT_SINK@")?;
 
         Ok(percent)
     }
@@ -109,32 +189,22 @@ impl Volume {
     /// Returns an error if the command cannot be spawned
     /// Returns an error if values in the output of the command cannot be parsed
     pub fn set_percent(percent_string: &str) -> Result<(), DaemonError> {
-        // If the percentage is a change, figure out the true percentage
-        let linear_percent = if percent_string.starts_with('+') || percent_string.starts_with('-') {
-            // Get the value of the percentage
-            let delta_percent = i32::try_from(
-                percent_string
-                    .trim_start_matches('+')
-                    .trim_start_matches('-')
-                    .to_string()
-                    .parse::<u32>()?,
-            )?;
+        let current_percent = Self::get_percent()?;
 
-            // Adjust the currently memorised volume
-            let new_percent = {
-                (i32::try_from(*VOLUME_PERCENT.lock().map_err(|_| DaemonError::MutexLockError)?)?
-                    + match percent_string.chars().next() {
-                        Some('+') => delta_percent,
-                        Some('-') => -delta_percent,
-                        _ => 0,
-                    })
-                .clamp(0, 100) as u32
-            };
+        // Changes stop at 100%, going past it requires a boost, but a boosted volume can still be lowered
+        Self::set_percent_with_limit(percent_string, current_percent.max(100))
+    }
 
-            new_percent
-        } else {
-            percent_string.parse::<u32>()?
-        };
+    /// # Errors
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn set_boost(percent_string: &str) -> Result<(), DaemonError> {
+        Self::set_percent_with_limit(percent_string, Self::get_max_percent()?)
+    }
+
+    fn set_percent_with_limit(percent_string: &str, limit: u32) -> Result<(), DaemonError> {
+        let current_percent = Self::get_percent()?;
+        let (linear_percent, _) = Self::apply_percent_string(current_percent, percent_string, limit)?;
 
         // Set the memorised volume
         {
@@ -143,13 +213,13 @@ impl Volume {
             *current_vol = linear_percent;
         }
 
-        // Set the volume internally as a logarithmic value
-        let logarithmic_percent = linear_to_logarithmic(f64::from(linear_percent));
+        // Set the volume internally using the configured curve
+        let device_percent = CONFIG.volume.curve.linear_to_device(f64::from(linear_percent));
 
         // Set the volume
         let _ = command::run(
             "wpctl",
-            &["set-volume", "@DEFAULT_SINK@", format!("{logarithmic_percent}%").as_str()],
+            &["set-volume", "@DEFAULT_SINK@", format!("{device_percent}%").as_str()],
         )?;
 
         Ok(())
@@ -160,73 +230,367 @@ impl Volume {
     /// Returns an error if values in the output of the command cannot be parsed
     pub fn set_mute(mute_string: &str) -> Result<(), DaemonError> {
         let mute = if mute_string == "toggle" {
-            mute_string.to_string()
+            !Self::get_mute()?
         } else {
-            u8::from(mute_string.parse::<bool>()?).to_string()
+            mute_string.parse::<bool>()?
         };
 
+        // Set the memorised mute state first, so the change isn't seen as external
+        *VOLUME_MUTE.lock().map_err(|_| DaemonError::MutexLockError)? = mute;
+
         // Set the mute state
-        let _ = command::run("wpctl", &["set-mute", "@DEFAULT_SINK@", mute.as_str()])?;
+        let _ = command::run(
+            "wpctl",
+            &["set-mute", "@DEFAULT_SINK@", u8::from(mute).to_string().as_str()],
+        )?;
 
         Ok(())
     }
 
-    #[must_use]
-    pub fn get_icon(percent: u32, muted: bool) -> String {
-        format!(
-            "audio-volume-{}",
-            if muted {
-                "muted"
-            } else {
-                match percent {
-                    0 => "muted",
-                    1..=33 => "low",
-                    34..=67 => "medium",
-                    68..=100 => "high",
-                    101.. => "overamplified",
+    /// Re-reads the default sink, replacing the memorised values if they were changed by another tool
+    ///
+    /// # Errors
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    /// Returns an error if the memorised volume mutexes cannot be locked
+    pub fn resync() -> Result<bool, DaemonError> {
+        let (volume, mute) = Self::get_node_raw("@DEFAULT_SINK@")?;
+
+        let percent_changed = {
+            let mut memorised_percent = VOLUME_PERCENT.lock().map_err(|_| DaemonError::MutexLockError)?;
+
+            // The memorised percent can only be compared up to the precision which wpctl reports
+            let expected_volume = CONFIG.volume.curve.linear_to_device(f64::from(*memorised_percent)) / 100.;
+            let percent_changed = (expected_volume - volume).abs() > WPCTL_PRECISION / 2. + f64::EPSILON;
+
+            if percent_changed {
+                *memorised_percent = CONFIG.volume.curve.device_to_linear_percent(volume * 100.);
+            }
+
+            percent_changed
+        };
+
+        let mute_changed =
+            std::mem::replace(&mut *VOLUME_MUTE.lock().map_err(|_| DaemonError::MutexLockError)?, mute) != mute;
+
+        Ok(percent_changed || mute_changed)
+    }
+
+    /// Follows ``pactl subscribe`` and resyncs the volume whenever a sink or the default sink changes
+    ///
+    /// # Errors
+    /// Returns an error if ``pactl`` cannot be spawned or its output cannot be read
+    /// Returns an error if the volume cannot be resynced
+    /// Returns an error if the update cannot be sent to the client handler
+    pub async fn monitor(clients_tx: mpsc::UnboundedSender<ClientMessage>) -> Result<(), DaemonError> {
+        let mut child = tokio::process::Command::new("pactl")
+            .arg("subscribe")
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| DaemonError::CommandError {
+                name: "pactl".to_string(),
+                args: vec!["subscribe".to_string()],
+                e: e.to_string(),
+            })?;
+
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| DaemonError::ParseError("pactl subscribe has no stdout".to_string()))?;
+        let mut lines = BufReader::new(stdout).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            // Only sink changes (Volume, mute) and server changes (Default sink) are relevant
+            if !(line.contains("on sink #") || line.contains("on server")) {
+                continue;
+            }
+
+            if Self::resync()? {
+                clients_tx.send(ClientMessage::UpdateVolume)?;
+
+                if CONFIG.volume.notify_external_changes {
+                    Self::notify()?;
                 }
             }
-        )
+        }
+
+        Ok(())
+    }
+
+    /// # Errors
+    /// Returns an error if ``pw-dump`` cannot be spawned
+    /// Returns an error if the output of ``pw-dump`` or ``wpctl`` cannot be parsed
+    pub fn get_apps() -> Result<Vec<AppStream>, DaemonError> {
+        let output = command::run("pw-dump", &[])?;
+        let objects: Vec<serde_json::Value> = serde_json::from_str(&output)?;
+
+        objects
+            .iter()
+            .filter(|object| object["type"] == "PipeWire:Interface:Node")
+            .filter(|object| object["info"]["props"]["media.class"] == "Stream/Output/Audio")
+            .map(|object| {
+                let props = &object["info"]["props"];
+
+                let id = object["id"]
+                    .as_u64()
+                    .ok_or_else(|| DaemonError::ParseError(object.to_string()))?;
+
+                // Fall back to the node name when the stream doesn't report an application
+                let name = props["application.name"]
+                    .as_str()
+                    .or_else(|| props["node.name"].as_str())
+                    .unwrap_or_default()
+                    .to_string();
+
+                // The process id is reported as either a number or a string
+                let pid = match &props["application.process.id"] {
+                    serde_json::Value::Number(pid) => pid.as_u64().map(u32::try_from).transpose()?,
+                    serde_json::Value::String(pid) => Some(pid.parse::<u32>()?),
+                    _ => None,
+                };
+
+                let id = u32::try_from(id)?;
+                let (percent, mute) = Self::get_node(id.to_string().as_str())?;
+
+                Ok(AppStream {
+                    id,
+                    name,
+                    pid,
+                    percent,
+                    mute,
+                })
+            })
+            .collect()
+    }
+
+    /// # Errors
+    /// Returns an error if the streams cannot be enumerated
+    /// Returns an error if no stream matches the given application name
+    pub fn get_app(name: &str) -> Result<Vec<AppStream>, DaemonError> {
+        let streams = Self::get_apps()?
+            .into_iter()
+            .filter(|stream| stream.name.eq_ignore_ascii_case(name))
+            .collect::<Vec<_>>();
+
+        if streams.is_empty() {
+            return Err(DaemonError::StreamNotFound(name.to_string()));
+        }
+
+        Ok(streams)
+    }
+
+    fn get_node_raw(node_id: &str) -> Result<(f64, bool), DaemonError> {
+        // Get the volume and mute status of the node as a string
+        let output = command::run("wpctl", &["get-volume", node_id])?;
+        let mut output_split = output.trim_start_matches("Volume: ").split_whitespace(); // Left with only volume number, and muted status
+
+        let volume = if let Some(volume_str) = output_split.next() {
+            volume_str.parse::<f64>()?
+        } else {
+            return Err(DaemonError::ParseError(output));
+        };
+
+        let mute = output_split.next().is_some();
+
+        Ok((volume, mute))
+    }
+
+    fn get_node(node_id: &str) -> Result<(u32, bool), DaemonError> {
+        let (volume, mute) = Self::get_node_raw(node_id)?;
+
+        // Convert the volume to a linear percentage
+        Ok((CONFIG.volume.curve.device_to_linear_percent(volume * 100.), mute))
+    }
+
+    /// # Errors
+    /// Returns an error if no stream matches the given application name
+    /// Returns an error if the command cannot be spawned
+    /// Returns an error if values in the output of the command cannot be parsed
+    pub fn set_app_percent(name: &str, percent_string: &str) -> Result<(), DaemonError> {
+        for stream in Self::get_app(name)? {
+            let (linear_percent, _) = Self::apply_percent_string(stream.percent, percent_string, 100)?;
+            let device_percent = CONFIG.volume.curve.linear_to_device(f64::from(linear_percent));
+
+            command::run(
+                "wpctl",
+                &[
+                    "set-volume",
+                    stream.id.to_string().as_str(),
+                    format!("{device_percent}%").as_str(),
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// # Errors
+    /// Returns an error if no stream matches the given application name
+    /// Returns an error if the command cannot be spawned
+    pub fn set_app_mute(name: &str, mute_string: &str) -> Result<(), DaemonError> {
+        let mute = if mute_string == "toggle" {
+            mute_string.to_string()
+        } else {
+            u8::from(mute_string.parse::<bool>()?).to_string()
+        };
+
+        for stream in Self::get_app(name)? {
+            command::run("wpctl", &["set-mute", stream.id.to_string().as_str(), mute.as_str()])?;
+        }
+
+        Ok(())
+    }
+
+    /// Works out the percentage which setting ``percent_string`` on the item would result in, and whether it is clamped
+    ///
+    /// # Errors
+    /// Returns an error if the memorised volume mutex cannot be locked
+    /// Returns an error if the percentage string cannot be parsed
+    pub fn get_target_percent(volume_item: &VolumeItem, percent_string: &str) -> Result<(u32, bool), DaemonError> {
+        let current_percent = Self::get_percent()?;
+
+        let limit = if matches!(volume_item, VolumeItem::Boost) {
+            Self::get_max_percent()?
+        } else {
+            current_percent.max(100)
+        };
+
+        Self::apply_percent_string(current_percent, percent_string, limit)
+    }
+
+    /// # Errors
+    /// Returns an error if the default sink cannot be inspected
+    pub fn get_max_percent() -> Result<u32, DaemonError> {
+        let max_percent = if CONFIG.volume.sink_max_percent.is_empty() {
+            CONFIG.volume.max_percent
+        } else {
+            let sink_name = Self::get_default_sink_name()?;
+
+            CONFIG
+                .volume
+                .sink_max_percent
+                .get(&sink_name)
+                .copied()
+                .unwrap_or(CONFIG.volume.max_percent)
+        };
+
+        // The ceiling can never be lower than the normal maximum
+        Ok(max_percent.max(100))
+    }
+
+    fn get_default_sink_name() -> Result<String, DaemonError> {
+        let output = command::run("wpctl", &["inspect", "@DEFAULT_SINK@"])?;
+
+        // Find the line which looks like `* node.name = "alsa_output..."`
+        output
+            .lines()
+            .find_map(|line| line.trim().trim_start_matches("* ").strip_prefix("node.name = "))
+            .map(|name| name.trim_matches('"').to_string())
+            .ok_or_else(|| DaemonError::ParseError(output.clone()))
+    }
+
+    // The percentage which the percent string gives, and whether it had to be limited to between 0 and the limit
+    fn apply_percent_string(
+        current_percent: u32,
+        percent_string: &str,
+        limit: u32,
+    ) -> Result<(u32, bool), DaemonError> {
+        // If the percentage is a change, figure out the true percentage
+        let percent = if percent_string.starts_with('+') || percent_string.starts_with('-') {
+            let delta_percent = i64::from(
+                percent_string
+                    .trim_start_matches('+')
+                    .trim_start_matches('-')
+                    .parse::<u32>()?,
+            );
+
+            i64::from(current_percent)
+                + match percent_string.chars().next() {
+                    Some('+') => delta_percent,
+                    Some('-') => -delta_percent,
+                    _ => 0,
+                }
+        } else {
+            i64::from(percent_string.parse::<u32>()?)
+        };
+
+        let limited_percent = percent.clamp(0, i64::from(limit));
+
+        Ok((limited_percent as u32, limited_percent != percent))
+    }
+
+    #[must_use]
+    pub fn get_icon(style: IconStyle, percent: u32, muted: bool) -> String {
+        CONFIG.icons.maps(style).volume.get(muted.then_some("muted"), percent)
     }
 
     /// # Errors
     /// Returns an error if the requested value could not be parsed
-    pub fn get_tuples() -> Result<Vec<(String, String)>, DaemonError> {
+    pub fn get_tuples() -> Result<Tuples, DaemonError> {
         let (percent, mute_state) = Self::get()?;
-        let icon = Self::get_icon(percent, mute_state);
+        let icon = Self::get_icon(IconStyle::Name, percent, mute_state);
+        let glyph = Self::get_icon(IconStyle::Glyph, percent, mute_state);
 
-        Ok(vec![
-            ("percent".to_string(), percent.to_string()),
-            ("mute_state".to_string(), mute_state.to_string()),
-            ("icon".to_string(), format!("{icon}{ICON_EXT}")),
-        ])
+        Ok([
+            vec![
+                ("percent".to_string(), percent.into()),
+                ("mute_state".to_string(), mute_state.into()),
+            ],
+            icon::to_tuples("", &icon, &glyph),
+        ]
+        .concat())
     }
 
     /// # Errors
     /// Returns an error if the requested value could not be parsed
     pub fn parse_item(item: DaemonItem, volume_item: &VolumeItem, value: Option<String>) -> Result<DaemonReply, DaemonError> {
+        if let VolumeItem::App { name, item: app_item } = volume_item {
+            return Self::parse_app_item(item, name, app_item, value);
+        }
+
         Ok(if let Some(value) = value {
             let prev_percent_and_mute = Self::get()?;
 
             // Set value
-            match volume_item {
-                VolumeItem::Percent => Self::set_percent(value.as_str())?,
-                VolumeItem::Mute => Self::set_mute(value.as_str())?,
-                _ => {}
-            }
+            let clamped = match volume_item {
+                VolumeItem::Percent | VolumeItem::Boost => {
+                    let (_, clamped) = Self::get_target_percent(volume_item, value.as_str())?;
+
+                    if matches!(volume_item, VolumeItem::Boost) {
+                        Self::set_boost(value.as_str())?;
+                    } else {
+                        Self::set_percent(value.as_str())?;
+                    }
+
+                    clamped
+                }
+                VolumeItem::Mute => {
+                    Self::set_mute(value.as_str())?;
+
+                    false
+                }
+                _ => false,
+            };
 
             let new_percent_and_mute = Self::get()?;
+            let changed = prev_percent_and_mute != new_percent_and_mute;
 
-            if prev_percent_and_mute != new_percent_and_mute {
+            if changed {
                 // Do a notification
                 Self::notify()?;
             }
 
-            DaemonReply::Value { item, value }
+            DaemonReply::Set {
+                item,
+                tuples: Self::get_tuples()?,
+                changed,
+                clamped,
+            }
         } else {
             // Get value
             match volume_item {
-                VolumeItem::Percent => DaemonReply::Value {
+                VolumeItem::Percent | VolumeItem::Boost => DaemonReply::Value {
                     item,
                     value: Self::get_percent()?.to_string(),
                 },
@@ -234,30 +598,121 @@ impl Volume {
                     item,
                     value: Self::get_mute()?.to_string(),
                 },
-                VolumeItem::Icon => {
+                VolumeItem::Icon(style) => {
                     let (percent, muted) = Self::get()?;
 
                     DaemonReply::Value {
                         item,
-                        value: Self::get_icon(percent, muted),
+                        value: Self::get_icon(style.unwrap_or(CONFIG.icons.style), percent, muted),
                     }
                 }
+                VolumeItem::Apps => DaemonReply::Value {
+                    item,
+                    value: serde_json::to_string(&Self::get_apps()?)?,
+                },
                 VolumeItem::All => DaemonReply::Tuples {
                     item,
                     tuples: Self::get_tuples()?,
                 },
+                // Applications are handled by parse_app_item
+                VolumeItem::App { .. } => return Err(DaemonError::UnhandledItem(format!("{item:?}"))),
+            }
+        })
+    }
+
+    fn parse_app_item(
+        item: DaemonItem,
+        name: &str,
+        app_item: &VolumeAppItem,
+        value: Option<String>,
+    ) -> Result<DaemonReply, DaemonError> {
+        Ok(if let Some(value) = value {
+            let prev_streams = Self::get_app(name)?;
+
+            // Set value
+            let clamped = match app_item {
+                VolumeAppItem::Percent => {
+                    // Each stream is changed from its own percentage
+                    let mut clamped = false;
+                    for stream in &prev_streams {
+                        clamped |= Self::apply_percent_string(stream.percent, value.as_str(), 100)?.1;
+                    }
+
+                    Self::set_app_percent(name, value.as_str())?;
+
+                    clamped
+                }
+                VolumeAppItem::Mute => {
+                    Self::set_app_mute(name, value.as_str())?;
+
+                    false
+                }
+                VolumeAppItem::All => false,
+            };
+
+            let new_streams = Self::get_app(name)?;
+            let changed = prev_streams != new_streams;
+
+            if changed {
+                // Do a notification
+                Self::notify_app(name)?;
+            }
+
+            // Values of the first stream, as with getting them
+            let stream = new_streams
+                .first()
+                .ok_or_else(|| DaemonError::StreamNotFound(name.to_string()))?;
+
+            DaemonReply::Set {
+                item,
+                tuples: vec![
+                    ("percent".to_string(), stream.percent.into()),
+                    ("mute".to_string(), stream.mute.into()),
+                ],
+                changed,
+                clamped,
+            }
+        } else {
+            let streams = Self::get_app(name)?;
+            let stream = streams
+                .first()
+                .ok_or_else(|| DaemonError::StreamNotFound(name.to_string()))?;
+
+            // Get value, using the first stream when an application has several
+            match app_item {
+                VolumeAppItem::Percent => DaemonReply::Value {
+                    item,
+                    value: stream.percent.to_string(),
+                },
+                VolumeAppItem::Mute => DaemonReply::Value {
+                    item,
+                    value: stream.mute.to_string(),
+                },
+                VolumeAppItem::All => DaemonReply::Value {
+                    item,
+                    value: serde_json::to_string(&streams)?,
+                },
             }
         })
     }
 
     #[must_use]
-    pub const fn match_get_commands(commands: &Option<VolumeGetCommands>) -> DaemonMessage {
+    pub fn match_get_commands(commands: &Option<VolumeGetCommands>) -> DaemonMessage {
         DaemonMessage::Get {
             item: match commands {
                 Some(commands) => match commands {
                     VolumeGetCommands::Percent => DaemonItem::Volume(VolumeItem::Percent),
                     VolumeGetCommands::Mute => DaemonItem::Volume(VolumeItem::Mute),
-                    VolumeGetCommands::Icon => DaemonItem::Volume(VolumeItem::Icon),
+                    VolumeGetCommands::Icon { style } => DaemonItem::Volume(VolumeItem::Icon(*style)),
+                    VolumeGetCommands::Apps => DaemonItem::Volume(VolumeItem::Apps),
+                    VolumeGetCommands::App { name, commands } => DaemonItem::Volume(VolumeItem::App {
+                        name: name.clone(),
+                        item: match commands {
+                            Some(VolumeAppGetCommands::Percent) => VolumeAppItem::Percent,
+                            Some(VolumeAppGetCommands::Mute) => VolumeAppItem::Mute,
+                            None => VolumeAppItem::All,
+                        },
+                    }),
                 },
                 None => DaemonItem::Volume(VolumeItem::All),
             },
@@ -267,17 +722,40 @@ impl Volume {
     #[must_use]
     pub fn match_set_commands(commands: VolumeSetCommands) -> DaemonMessage {
         match commands {
-            VolumeSetCommands::Percent { value } => DaemonMessage::Set {
-                item: DaemonItem::Volume(VolumeItem::Percent),
-                value,
-            },
+            VolumeSetCommands::Percent { value, fade } => Self::set_or_fade(VolumeItem::Percent, value, fade),
             VolumeSetCommands::Mute { value } => DaemonMessage::Set {
                 item: DaemonItem::Volume(VolumeItem::Mute),
-                value: value.map_or("toggle".to_string(), |value| value.to_string()),
+                value: value.map_or_else(|| "toggle".to_string(), |value| value.to_string()),
+            },
+            VolumeSetCommands::Boost { value, fade } => Self::set_or_fade(VolumeItem::Boost, value, fade),
+            VolumeSetCommands::App { name, commands } => match commands {
+                VolumeAppSetCommands::Percent { value } => DaemonMessage::Set {
+                    item: DaemonItem::Volume(VolumeItem::App {
+                        name,
+                        item: VolumeAppItem::Percent,
+                    }),
+                    value,
+                },
+                VolumeAppSetCommands::Mute { value } => DaemonMessage::Set {
+                    item: DaemonItem::Volume(VolumeItem::App {
+                        name,
+                        item: VolumeAppItem::Mute,
+                    }),
+                    value: value.map_or_else(|| "toggle".to_string(), |value| value.to_string()),
+                },
             },
         }
     }
 
+    const fn set_or_fade(volume_item: VolumeItem, value: String, fade: Option<u64>) -> DaemonMessage {
+        let item = DaemonItem::Volume(volume_item);
+
+        match fade {
+            Some(duration) => DaemonMessage::Fade { item, value, duration },
+            None => DaemonMessage::Set { item, value },
+        }
+    }
+
     #[must_use]
     pub const fn match_update_commands(commands: &VolumeUpdateCommands) -> DaemonMessage {
         match commands {
@@ -295,25 +773,75 @@ impl Volume {
     pub fn notify() -> Result<(), DaemonError> {
         let (percent, muted) = Self::get()?;
 
-        let icon = Self::get_icon(percent, muted);
+        let icon = Self::get_icon(IconStyle::Name, percent, muted);
 
-        command::run(
-            "dunstify",
+        // Above 100% the progress bar shows how far into the boost range the volume is
+        let (summary, value) = if percent > 100 {
+            let boost_range = Self::get_max_percent()?.saturating_sub(100).max(1);
+
+            ("Volume Boost: ", ((percent - 100) * 100 / boost_range).min(100))
+        } else {
+            ("Volume: ", percent)
+        };
+
+        Notification::new(
+            &CONFIG.notifications.volume,
+            summary,
+            &icon,
+            &[("percent", percent.to_string().as_str())],
+        )
+        .value(value)
+        .send()
+    }
+
+    /// # Errors
+    /// Returns an error if no stream matches the given application name
+    /// Returns an error if the requested value could not be parsed
+    pub fn notify_app(name: &str) -> Result<(), DaemonError> {
+        let streams = Self::get_app(name)?;
+        let stream = streams
+            .first()
+            .ok_or_else(|| DaemonError::StreamNotFound(name.to_string()))?;
+
+        let icon = Self::get_icon(IconStyle::Name, stream.percent, stream.mute);
+
+        Notification::new(
+            &CONFIG.notifications.volume,
+            "{device}: ",
+            &icon,
             &[
-                "-u",
-                "normal",
-                "-r",
-                format!("{NOTIFICATION_ID}").as_str(),
-                "-i",
-                icon.trim().to_string().as_str(),
-                "-t",
-                format!("{NOTIFICATION_TIMEOUT}").as_str(),
-                "-h",
-                format!("int:value:{percent}").as_str(),
-                "Volume: ",
+                ("percent", stream.percent.to_string().as_str()),
+                ("device", stream.name.as_str()),
             ],
-        )?;
+        )
+        .value(stream.percent)
+        .send()
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_percent_string() {
+        assert_eq!(Volume::apply_percent_string(40, "60", 100).ok(), Some((60, false)));
+        assert_eq!(Volume::apply_percent_string(40, "+5", 100).ok(), Some((45, false)));
+        assert_eq!(Volume::apply_percent_string(40, "-5", 100).ok(), Some((35, false)));
+    }
+
+    #[test]
+    fn clamps_percent_string() {
+        assert_eq!(Volume::apply_percent_string(98, "+5", 100).ok(), Some((100, true)));
+        assert_eq!(Volume::apply_percent_string(3, "-5", 100).ok(), Some((0, true)));
+        assert_eq!(Volume::apply_percent_string(40, "150", 100).ok(), Some((100, true)));
+        assert_eq!(Volume::apply_percent_string(140, "+20", 150).ok(), Some((150, true)));
+        assert_eq!(Volume::apply_percent_string(95, "+5", 100).ok(), Some((100, false)));
+    }
+
+    #[test]
+    fn rejects_invalid_percent_string() {
+        assert!(Volume::apply_percent_string(40, "loud", 100).is_err());
+        assert!(Volume::apply_percent_string(40, "+abc", 100).is_err());
     }
 }
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use crate::{
+    config::CONFIG,
+    error::DaemonError,
+    notification::render,
+    output::{get_percentage, placeholders},
+    tuples::{TupleValue, TUPLE_NAMES},
+};
+
+/// A CSS class which is added while the module's percentage is between ``min`` and ``max`` (Inclusive),
+/// or while the tuple ``key`` is true when it is given
+#[derive(Deserialize, Debug, Clone)]
+pub struct WaybarClass {
+    pub key: Option<String>,
+    pub min: Option<u32>,
+    pub max: Option<u32>,
+    pub class: String,
+}
+
+impl WaybarClass {
+    fn new(min: Option<u32>, max: Option<u32>, class: &str) -> Self {
+        Self {
+            key: None,
+            min,
+            max,
+            class: class.to_string(),
+        }
+    }
+
+    fn with_key(key: &str, class: &str) -> Self {
+        Self {
+            key: Some(key.to_string()),
+            min: None,
+            max: None,
+            class: class.to_string(),
+        }
+    }
+
+    fn matches(&self, tuples: &[(String, TupleValue)], percentage: Option<u32>) -> bool {
+        match &self.key {
+            Some(key) => tuples
+                .iter()
+                .any(|(tuple_key, value)| tuple_key == key && *value == TupleValue::Bool(true)),
+            None => percentage.is_some_and(|percentage| {
+                self.min.is_none_or(|min| percentage >= min) && self.max.is_none_or(|max| percentage <= max)
+            }),
+        }
+    }
+}
+
+/// How a module is shown in a Waybar custom module, the templates take the module's ``{key}`` placeholders
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WaybarModule {
+    pub text: String,
+    pub tooltip: String,
+    /// Key of the tuple which is used as the percentage, and for the classes
+    pub percentage: Option<String>,
+    /// Used by Waybar to pick a ``format-icons`` entry
+    pub alt: String,
+    pub classes: Vec<WaybarClass>,
+}
+
+impl Default for WaybarModule {
+    fn default() -> Self {
+        Self {
+            text: "{glyph}".to_string(),
+            tooltip: String::new(),
+            percentage: None,
+            alt: "{icon}".to_string(),
+            classes: Vec::new(),
+        }
+    }
+}
+
+impl WaybarModule {
+    fn with_percentage(text: &str, tooltip: &str, key: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            tooltip: tooltip.to_string(),
+            percentage: Some(key.to_string()),
+            ..Self::default()
+        }
+    }
+
+    fn with_text(text: &str, tooltip: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            tooltip: tooltip.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// The JSON of the module, in the format which Waybar reads from a custom module's ``exec``
+    #[must_use]
+    pub fn to_json(&self, tuples: &[(String, TupleValue)]) -> Value {
+        let placeholders = placeholders(tuples);
+
+        let percentage = self.percentage.as_ref().and_then(|key| get_percentage(tuples, key));
+
+        let classes = self
+            .classes
+            .iter()
+            .filter(|class| class.matches(tuples, percentage))
+            .map(|class| class.class.clone())
+            .collect::<Vec<_>>();
+
+        let mut json = json!({
+            "text": render(&self.text, &placeholders),
+            "tooltip": render(&self.tooltip, &placeholders),
+            "class": classes,
+            "alt": render(&self.alt, &placeholders),
+        });
+
+        if let (Some(percentage), Some(object)) = (percentage, json.as_object_mut()) {
+            object.insert("percentage".to_string(), json!(percentage));
+        }
+
+        json
+    }
+}
+
+/// Waybar settings of each module, keyed by the module's name in ``get``
+#[derive(Debug)]
+pub struct WaybarConfig(pub HashMap<String, WaybarModule>);
+
+impl Default for WaybarConfig {
+    fn default() -> Self {
+        Self(HashMap::from([
+            (
+                "volume".to_string(),
+                WaybarModule {
+                    classes: vec![WaybarClass::with_key("mute_state", "muted")],
+                    ..WaybarModule::with_percentage("{glyph} {percent}%", "Volume: {percent}%", "percent")
+                },
+            ),
+            (
+                "brightness".to_string(),
+                WaybarModule::with_percentage(
+                    "{glyph} {monitor_percent}%",
+                    "Monitor: {monitor_percent}%\nKeyboard: {keyboard_percent}%",
+                    "monitor_percent",
+                ),
+            ),
+            (
+                "battery".to_string(),
+                WaybarModule {
+                    classes: vec![
+                        WaybarClass::new(None, Some(15), "critical"),
+                        WaybarClass::new(Some(16), Some(30), "warning"),
+                    ],
+                    ..WaybarModule::with_percentage("{glyph} {percent}%", "{state_label}, {time}", "percent")
+                },
+            ),
+            (
+                "ram".to_string(),
+                WaybarModule {
+                    classes: vec![WaybarClass::new(Some(90), None, "critical")],
+                    ..WaybarModule::with_percentage("{glyph} {percent}%", "{used} / {total}", "percent")
+                },
+            ),
+            (
+                "bluetooth".to_string(),
+                WaybarModule::with_text("{glyph}", "Bluetooth: {state}"),
+            ),
+            (
+                "fan_profile".to_string(),
+                WaybarModule::with_text("{glyph} {profile_label}", "Fan profile: {profile_label}"),
+            ),
+            (
+                "dnd".to_string(),
+                WaybarModule::with_text("{glyph}", "Do not disturb: {state}"),
+            ),
+        ]))
+    }
+}
+
+impl<'de> Deserialize<'de> for WaybarConfig {
+    // Modules in the config file replace their defaults, the others are kept
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let modules = HashMap::<String, WaybarModule>::deserialize(deserializer)?;
+
+        let mut config = Self::default();
+        config.0.extend(modules);
+
+        Ok(config)
+    }
+}
+
+/// # Errors
+/// Returns an error if the module is not one of the modules in ``get``
+pub fn get_module(name: &str) -> Result<&'static WaybarModule, DaemonError> {
+    CONFIG.waybar.0.get(name).ok_or_else(|| {
+        DaemonError::ParseError(format!(
+            "Unknown module '{name}' for Waybar. Use one of {}.",
+            TUPLE_NAMES.join(", ")
+        ))
+    })
+}